@@ -29,6 +29,9 @@ impl eframe::App for DemoApp {
                 ExGrid::new("some_unique_id")
                     .mode(GridMode::CompactWidth)
                     // .mode(GridMode::Traditional)
+                    // .mode(GridMode::Auto0Wrap)
+                    // .mode(GridMode::AutoOptimalWrap)
+                    // .search_box(true)
                     .show(ui, |ui| {
                         ui.start_collapsing();
                         ui.extext("Data");
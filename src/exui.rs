@@ -3,6 +3,7 @@ use maybe_owned::MaybeOwnedMut;
 use once_cell::sync::Lazy;
 use std::any::Any;
 use std::borrow::BorrowMut;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 
 use crate::*;
@@ -25,14 +26,92 @@ pub(crate) struct ExUiInner {
     pub(crate) column: usize,
     pub(crate) collapsing_header: bool,
     pub(crate) row_cursor: Vec<usize>,
+    /// Keys of rows at `row_cursor` on each nesting level (see [`ExUi::row_key`])
+    pub(crate) row_keys: Vec<Option<Id>>,
     pub(crate) width_max_prev: f32,
     pub(crate) width_max: f32,
+    /// Index of current row of `egui::Grid` (Traditional mode)
+    pub(crate) row: usize,
+    /// Index of the first row of `egui::Grid` (not zero if it is shown from [`ExGrid::start_row`])
+    pub(crate) first_row: usize,
+    pub(crate) min_row_height: f32,
+    /// Row heights measured in previous frame (Traditional mode), starting from `first_row_prev` row
+    pub(crate) row_heights_prev: Vec<f32>,
+    pub(crate) first_row_prev: usize,
+    pub(crate) row_heights: Vec<f32>,
+    /// Widths of cells content measured in current frame (Traditional mode)
+    pub(crate) col_widths: Vec<f32>,
+    /// Right edges of columns measured in current frame (Traditional mode)
+    pub(crate) col_right: Vec<f32>,
+    /// Widths of cells content measured in previous frame (Traditional mode)
+    pub(crate) col_widths_prev: Vec<f32>,
+    /// Widths of columns set by resizing (Traditional mode), `None` means column fits its content
+    pub(crate) column_widths: Vec<Option<f32>>,
+    /// Layout of cells of columns set by [`ExGrid::column`]
+    pub(crate) columns: Vec<ColumnSpec>,
+    /// Minimal width of columns of `egui::Grid`
+    pub(crate) min_col_width: f32,
+    /// Rect of cells of current row (Traditional mode)
+    pub(crate) row_rect: Rect,
     /// 0 means widgets will be added in enabled state,
     /// higher numbers indicate number of `Self::start_disabled` not matched by `Self::stop_disabled`
     pub(crate) disabled: usize,
+    /// Some cell of the current row was added in disabled state
+    pub(crate) row_disabled: bool,
+    /// Hover text of the current row shown if it is disabled (see [`ExUi::disabled_reason`])
+    pub(crate) disabled_reason: Option<WidgetText>,
 
     pub(crate) mode: ExUiMode,
     pub(crate) collapsed: Vec<bool>,
+    /// Collapse state is kept with persisted data (see [`ExGrid::persist_collapsed`])
+    pub(crate) persist_collapsed: bool,
+    /// Expanding and collapsing of rows is animated (see [`ExGrid::animate`])
+    pub(crate) animate: bool,
+    /// Paints icon of collapsing header instead of default button (see [`ExGrid::collapse_icon`])
+    pub(crate) collapse_icon: Option<CollapseIconFn>,
+    /// Shown in place of collapsed rows (see [`ExGrid::collapsed_placeholder`])
+    pub(crate) collapsed_placeholder: Option<PlaceholderFn>,
+    /// Click anywhere in collapsing header row toggles it (see [`ExGrid::clickable_headers`])
+    pub(crate) clickable_headers: bool,
+    /// Width of indentation of each nesting level (see [`ExGrid::indent_width`])
+    pub(crate) indent_width: f32,
+    /// Bodies of collapsing rows being added, innermost last
+    pub(crate) bodies: Vec<RowsBody>,
+    /// Unions of responses of rows in bodies of [`ExUi::collapsing`] being added, innermost last
+    pub(crate) body_responses: Vec<Option<Response>>,
+    /// Changes of collapse state requested in previous frame
+    pub(crate) collapse_request: Option<CollapseRequest>,
+    /// Header row has buttons expanding and collapsing all rows (see [`ExGrid::expand_buttons`])
+    pub(crate) expand_buttons: bool,
+    /// Id of the grid (see [`ExGridResponse::id`]), base of ids of rows with keys
+    pub(crate) grid_id: Id,
+    pub(crate) compact_frame: Option<FramePickerFn>,
+    pub(crate) color_picker: Option<ColorPickerFn>,
+    /// Size of the grid in previous frame
+    pub(crate) size_prev: Vec2,
+    /// Header row is being added
+    pub(crate) header: bool,
+    pub(crate) sticky_header: Option<StickyHeader>,
+    /// Labels of columns (collected from header row), used in compact mode
+    pub(crate) column_names: Vec<String>,
+    pub(crate) compact_labels: CompactLabels,
+    /// Width of the widest column label (`CompactLabels::KeyValue` only)
+    pub(crate) label_width: Option<f32>,
+    /// Measurements of top level rows (only when shown by [`ExGrid::show_rows`])
+    pub(crate) row_blocks: Option<RowBlocks>,
+    pub(crate) sort: Option<ColumnSort>,
+    /// Order in which top level rows are added (set by [`ExUi::sort_order`]),
+    /// ids of rows are based on their index in data rather than on their position in the grid
+    pub(crate) sort_order: Option<Vec<usize>>,
+    /// Rows filtering (only if filter query is not empty)
+    pub(crate) filter: Option<RowFilter>,
+    pub(crate) selection: Option<Selection>,
+    /// Rects of rows shown in previous frame (rows are interactive where they were in previous frame)
+    pub(crate) row_rects_prev: HashMap<Id, Rect>,
+    pub(crate) row_rects: HashMap<Id, Rect>,
+    /// Interaction over current row united with responses of its cells
+    pub(crate) row_response: Option<Response>,
+    pub(crate) validation: Validation,
 }
 
 impl Default for ExUiInner {
@@ -42,13 +121,448 @@ impl Default for ExUiInner {
             collapsing_header: false,
             width_max_prev: 0.0,
             width_max: 0.0,
+            row: 0,
+            first_row: 0,
+            min_row_height: 0.0,
+            row_heights_prev: Vec::new(),
+            first_row_prev: 0,
+            row_heights: Vec::new(),
+            col_widths: Vec::new(),
+            col_right: Vec::new(),
+            col_widths_prev: Vec::new(),
+            column_widths: Vec::new(),
+            columns: Vec::new(),
+            min_col_width: 0.0,
+            row_rect: Rect::NOTHING,
             disabled: 0,
+            row_disabled: false,
+            disabled_reason: None,
             row_cursor: vec![0],
+            row_keys: vec![None],
             mode: Default::default(),
             collapsed: vec![false],
+            persist_collapsed: false,
+            animate: false,
+            collapse_icon: None,
+            collapsed_placeholder: None,
+            clickable_headers: false,
+            indent_width: 0.0,
+            bodies: Vec::new(),
+            body_responses: Vec::new(),
+            collapse_request: None,
+            expand_buttons: false,
+            grid_id: Id::new("exgrid"),
+            compact_frame: None,
+            color_picker: None,
+            size_prev: Vec2::ZERO,
+            header: false,
+            sticky_header: None,
+            column_names: Vec::new(),
+            compact_labels: Default::default(),
+            label_width: None,
+            row_blocks: None,
+            sort: None,
+            sort_order: None,
+            filter: None,
+            selection: None,
+            row_rects_prev: HashMap::new(),
+            row_rects: HashMap::new(),
+            row_response: None,
+            validation: Default::default(),
         }
     }
 }
+impl ExUiInner {
+    /// Frame of the row at `depth` nesting level (`CompactWidth` mode)
+    pub(crate) fn compact_frame(&self, depth: usize, style: &Style) -> Frame {
+        let mut frame = match self.compact_frame {
+            Some(ref frame_picker) => frame_picker(depth, style),
+            None => Frame::group(style),
+        };
+        if let Some(color) = self.color_picker.as_ref().and_then(|f| f(self.row, style)) {
+            frame.fill = color;
+        }
+        frame
+    }
+    /// Height of the current row measured in previous frame (Traditional mode)
+    fn row_height_prev(&self) -> f32 {
+        let row = self.row.checked_sub(self.first_row_prev);
+        let height = row.and_then(|row| self.row_heights_prev.get(row));
+        height.copied().unwrap_or(0.0).max(self.min_row_height)
+    }
+    /// Position below which rows are not shown as their collapsing headers are being animated
+    fn reveal_limit(&self) -> Option<f32> {
+        let limits = self.bodies.iter().filter_map(|body| body.limit);
+        limits.reduce(f32::min)
+    }
+    /// Range of widths of cells of `column` (Traditional mode):
+    /// exact width if column was resized, otherwise limits set by its [`ColumnSpec`]
+    fn width_limits(&self, column: usize) -> (f32, f32) {
+        let spec = self.columns.get(column);
+        let min = spec.and_then(|spec| spec.min).unwrap_or(0.0);
+        let max = spec.and_then(|spec| spec.max).unwrap_or(f32::INFINITY);
+        let max = max.max(min);
+        match self.column_widths.get(column).copied().flatten() {
+            Some(width) => {
+                let width = width.max(min).min(max);
+                (width, width)
+            }
+            None => (min, max),
+        }
+    }
+}
+/// Layout measurements of [`ExUi`] kept between frames
+#[derive(Clone, Default)]
+pub(crate) struct ExUiMemory {
+    width_max: f32,
+    row_heights: Vec<f32>,
+    first_row: usize,
+    size: Vec2,
+    row_rects: HashMap<Id, Rect>,
+    col_widths: Vec<f32>,
+}
+
+/// Subrows of collapsing header that are being added
+pub(crate) struct RowsBody {
+    /// Id of the collapsing header
+    id: Id,
+    /// Nesting level of subrows (index in [`ExUiInner::collapsed`])
+    level: usize,
+    /// Position of the first subrow
+    top: f32,
+    /// Subrows are being expanded or collapsed, they are clipped at this position (see [`ExGrid::animate`])
+    limit: Option<f32>,
+    /// Position of the elbow of the tree guide of the last subrow (Traditional mode)
+    guide_end: Option<f32>,
+}
+
+/// Changes of collapse state requested from outside of the grid (see [`ExGrid::expand_to_depth`]),
+/// they are applied to each collapsing header next time it is shown
+#[derive(Clone, Default)]
+pub(crate) struct CollapseRequest {
+    /// Rows nested at least this deep are collapsed, others are expanded (see [`ExGrid::expand_to_depth`])
+    depth: Option<usize>,
+    /// Collapsing headers to which the request was already applied (or which were set directly, see [`ExGrid::set_collapsed`]),
+    /// with [`ExGrid::show_rows`] headers out of view are not shown in the same frame
+    applied: HashSet<Id>,
+}
+impl CollapseRequest {
+    pub(crate) fn update(ctx: &Context, grid_id: Id, update: impl FnOnce(&mut Self)) {
+        let id = grid_id.with("collapse_request");
+        ctx.data_mut(|d| update(d.get_temp_mut_or_default(id)));
+        ctx.request_repaint();
+    }
+    pub(crate) fn take(ctx: &Context, grid_id: Id) -> Option<Self> {
+        let id = grid_id.with("collapse_request");
+        let request = ctx.data_mut(|d| d.get_temp(id));
+        ctx.data_mut(|d| d.remove::<Self>(id));
+        request
+    }
+    /// Keep request for headers not shown yet (unless all rows were shown or newer request was made meanwhile)
+    pub(crate) fn keep(mut self, ctx: &Context, grid_id: Id, all_rows_shown: bool) {
+        let id = grid_id.with("collapse_request");
+        if all_rows_shown || self.depth.is_none() {
+            return;
+        }
+        ctx.data_mut(|d| match d.get_temp::<Self>(id) {
+            Some(newer) if newer.depth.is_some() => {}
+            // headers set directly meanwhile
+            Some(newer) => {
+                self.applied.extend(newer.applied);
+                d.insert_temp(id, self);
+            }
+            None => d.insert_temp(id, self),
+        });
+    }
+    pub(crate) fn expand_to_depth(&mut self, depth: usize) {
+        self.depth = Some(depth);
+        self.applied.clear();
+    }
+    /// Set collapse state of collapsing header `id` of the grid `grid_id` (it is not overridden by pending request)
+    pub(crate) fn set_collapsed(ctx: &Context, grid_id: Id, id: Id, collapsed: bool) {
+        let persist = ctx.data_mut(|d| d.get_temp(grid_id.with("persist_collapsed")));
+        store_collapsed(ctx, id, collapsed, persist.unwrap_or(false));
+        Self::update(ctx, grid_id, |request| {
+            request.applied.insert(id);
+        });
+    }
+    /// Requested state of collapsing header `id` nested `depth` levels deep, if it was not applied yet
+    fn apply(&mut self, id: Id, depth: usize) -> Option<bool> {
+        let max_depth = self.depth?;
+        self.applied.insert(id).then_some(depth >= max_depth)
+    }
+}
+
+/// Rows filtering state (see [`ExGrid::filter`]),
+/// as rows are shown before their subrows, rows shown in the current frame are based on matches found in the previous one
+#[derive(Clone, Default)]
+pub(crate) struct RowFilter {
+    /// Text of the search box
+    pub(crate) text: String,
+    /// Lowercase query
+    pub(crate) query: String,
+    /// Rows that match the query or have matching subrows
+    pub(crate) shown: HashSet<Id>,
+    /// Collapsing headers with matching subrows (they are shown expanded)
+    pub(crate) open: HashSet<Id>,
+    /// Same as `shown`, but collected in current frame
+    pub(crate) shown_next: HashSet<Id>,
+    /// Same as `open`, but collected in current frame
+    pub(crate) open_next: HashSet<Id>,
+    /// Current row matches the query
+    row_matched: bool,
+}
+
+/// Row selection state (see [`ExGrid::selection`]), rows are identified by their paths (see [`ExUi::get_nesting_cursor`])
+#[derive(Clone, Default)]
+pub(crate) struct Selection {
+    pub(crate) multi: bool,
+    pub(crate) selected: Vec<Vec<usize>>,
+    /// Row from which range selection (with Shift) starts
+    anchor: Option<Vec<usize>>,
+    /// Rows shown in current frame (in order)
+    rows: Vec<Vec<usize>>,
+    /// Row clicked in current frame, with modifiers pressed
+    clicked: Option<(Vec<usize>, Modifiers)>,
+    /// Focused row from which focus is moved with ArrowUp (`true`) or ArrowDown in current frame, with modifiers pressed
+    key_move: Option<(Vec<usize>, bool, Modifiers)>,
+    /// Row that gets focus in the next frame (moved to with keyboard)
+    focus: Option<Vec<usize>>,
+    /// Place for background of current row (Traditional mode)
+    background: Option<ShapeIdx>,
+}
+
+impl Selection {
+    /// Apply click registered in this frame and prepare state for the next one
+    pub(crate) fn finish(&mut self) {
+        if let Some((path, up, modifiers)) = self.key_move.take() {
+            let position = self.rows.iter().position(|row| *row == path);
+            let target = match position {
+                Some(i) if up => i.checked_sub(1),
+                Some(i) => Some(i + 1),
+                None => None,
+            };
+            if let Some(target) = target.and_then(|i| self.rows.get(i)) {
+                self.focus = Some(target.clone());
+                // with Ctrl/Cmd focus is moved without changing selection
+                if !modifiers.command {
+                    self.clicked = Some((target.clone(), modifiers));
+                }
+            }
+        }
+        if let Some((path, modifiers)) = self.clicked.take() {
+            let position = |path: &Vec<usize>| self.rows.iter().position(|row| row == path);
+            let anchor = self.anchor.as_ref().and_then(position);
+            match (position(&path), anchor) {
+                (Some(end), Some(start)) if self.multi && modifiers.shift => {
+                    if !modifiers.command {
+                        self.selected.clear();
+                    }
+                    let range = start.min(end)..=start.max(end);
+                    for row in &self.rows[range] {
+                        if !self.selected.contains(row) {
+                            self.selected.push(row.clone());
+                        }
+                    }
+                }
+                _ if self.multi && modifiers.command => {
+                    match self.selected.iter().position(|row| *row == path) {
+                        Some(i) => drop(self.selected.remove(i)),
+                        None => self.selected.push(path.clone()),
+                    }
+                    self.anchor = Some(path);
+                }
+                _ => {
+                    self.selected = vec![path.clone()];
+                    self.anchor = Some(path);
+                }
+            }
+        }
+        if !self.multi {
+            self.selected.truncate(1);
+        }
+        self.rows.clear();
+    }
+}
+
+/// Validation of calls that have to be balanced (collapsing, keep_cell, disabled),
+/// it is enabled by `log` feature (mismatches are logged as warnings) or in debug builds (mismatches are painted below the grid)
+#[derive(Default)]
+pub(crate) struct Validation {
+    errors: Vec<String>,
+    /// Row cursors at which `start_collapsing` calls not matched yet were made
+    collapsing: Vec<Vec<usize>>,
+    /// Row cursors at which `start_disabled` calls not matched yet were made
+    disabled: Vec<Vec<usize>>,
+    /// Row cursor at which `keep_cell_start` of the current cell was called
+    keep_cell: Option<Vec<usize>>,
+}
+
+impl Validation {
+    const ENABLED: bool = cfg!(any(feature = "log", debug_assertions));
+
+    fn error(&mut self, error: &str, row_cursor: &[usize]) {
+        if Self::ENABLED {
+            self.errors
+                .push(format!("ExGrid: {error} (row cursor: {row_cursor:?})"));
+        }
+    }
+
+    /// Report mismatches found in the frame
+    fn report(&mut self, _ui: &Ui) {
+        #[cfg(feature = "log")]
+        for error in &self.errors {
+            log::warn!("{error}");
+        }
+        #[cfg(debug_assertions)]
+        {
+            let mut pos = _ui.min_rect().left_bottom();
+            for error in &self.errors {
+                pos.y = _ui.painter().error(pos, error).bottom();
+            }
+        }
+        self.errors.clear();
+    }
+}
+
+/// Heights of top level rows (together with their subrows) measured in current frame,
+/// used by [`ExGrid::show_rows`] to estimate position of rows that are not shown
+pub(crate) struct RowBlocks {
+    /// Top level row being added
+    index: usize,
+    /// Top of the row being added
+    top: f32,
+    /// `egui::Grid` row at which row being added starts
+    row: usize,
+    /// Height of the header row (see [`ExUi::header_row`])
+    pub(crate) header: Option<f32>,
+    /// Top level row, its height (spacing included) and number of `egui::Grid` rows it spans
+    pub(crate) measured: Vec<(usize, f32, usize)>,
+}
+
+impl RowBlocks {
+    pub(crate) fn new(index: usize, top: f32, row: usize) -> Self {
+        RowBlocks {
+            index,
+            top,
+            row,
+            header: None,
+            measured: Vec::new(),
+        }
+    }
+
+    /// Called at the end of each row, `index` is top level row that will be added next
+    fn measure(&mut self, index: usize, top: f32, row: usize, header: bool) {
+        if header {
+            self.header = Some(top - self.top);
+        } else if index != self.index {
+            self.measured
+                .push((self.index, top - self.top, row - self.row));
+            self.index = index;
+        } else {
+            return;
+        }
+        self.top = top;
+        self.row = row;
+    }
+}
+
+/// Header row in Traditional mode, it is drawn above the rest of the grid,
+/// so that it can stay visible at the top of the `ScrollArea`
+pub(crate) struct StickyHeader {
+    /// Painter of the layer on which header is drawn
+    painter: Painter,
+    background: ShapeIdx,
+    /// Vertical offset of the header (from its place in the grid), necessary to keep it visible
+    offset: f32,
+    rect: Rect,
+}
+
+impl StickyHeader {
+    fn new(ui: &Ui, row_height: f32, grid_height: f32) -> Self {
+        // without sublayers header can not be drawn above following rows, so it is not moved
+        let offset = if cfg!(any(feature = "egui28", feature = "egui29")) {
+            let top = ui.available_rect_before_wrap().top();
+            let max_offset = (grid_height - row_height).max(0.0);
+            (ui.clip_rect().top() - top).clamp(0.0, max_offset)
+        } else {
+            0.0
+        };
+        #[cfg(any(feature = "egui28", feature = "egui29"))]
+        let painter = {
+            let layer_id = LayerId::new(ui.layer_id().order, ui.id().with("header"));
+            // layer has to be ordered (placed directly above parent) to be interactable
+            ui.ctx().move_to_top(layer_id);
+            ui.ctx().set_sublayer(ui.layer_id(), layer_id);
+            ui.painter().clone().with_layer_id(layer_id)
+        };
+        #[cfg(not(any(feature = "egui28", feature = "egui29")))]
+        let painter = ui.painter().clone();
+        StickyHeader {
+            background: painter.add(Shape::Noop),
+            painter,
+            offset,
+            rect: Rect::NOTHING,
+        }
+    }
+
+    /// Sub-ui for single cell of the header
+    fn cell_ui(&self, ui: &mut Ui, max_rect: Rect, column: usize, align: Align) -> Ui {
+        let max_rect = max_rect.translate(vec2(0.0, self.offset));
+        let id = ui.id().with(("header", column));
+        let ctx = ui.ctx().clone();
+        let layer_id = self.painter.layer_id();
+        #[cfg(feature = "egui29")]
+        let mut root = Ui::new(
+            ctx,
+            layer_id,
+            id,
+            UiBuilder {
+                max_rect: Some(max_rect),
+                style: Some(ui.style().clone()),
+                ..Default::default()
+            },
+        );
+        #[cfg(not(feature = "egui29"))]
+        let mut root = Ui::new(
+            ctx,
+            layer_id,
+            id,
+            max_rect,
+            ui.clip_rect(),
+            #[cfg(feature = "egui28")]
+            Default::default(),
+        );
+        #[cfg(not(feature = "egui29"))]
+        root.set_style(ui.style().clone());
+        root.set_clip_rect(ui.clip_rect());
+        if !ui.is_enabled() {
+            root.disable();
+        }
+        let mut cell = cell_child_ui(&mut root, max_rect, cell_layout(align), id);
+        inherit_grid_wrap(ui, &mut cell);
+        cell
+    }
+
+    fn paint(&self, ui: &Ui, width: f32) {
+        if !self.rect.is_positive() {
+            return;
+        }
+        let visuals = ui.visuals();
+        let mut rect = self
+            .rect
+            .expand2(vec2(0.0, 0.5 * ui.spacing().item_spacing.y));
+        rect.min.x = ui.min_rect().left();
+        rect.max.x = rect.max.x.max(rect.min.x + width);
+        let stroke = visuals.widgets.noninteractive.bg_stroke;
+        let shape = Shape::Vec(vec![
+            Shape::rect_filled(rect, 0.0, visuals.extreme_bg_color),
+            Shape::hline(rect.x_range(), rect.bottom(), stroke),
+        ]);
+        self.painter.set(self.background, shape);
+    }
+}
+
 pub struct ExUiKeepCell<'a> {
     ui: MaybeOwnedMut<'a, Ui>,
     widgets_in_cell: usize,
@@ -95,17 +609,245 @@ impl<'a, 'b> ExUi<'a, 'b> {
             .as_ref()
             .map(|ui| {
                 if ui.is_visible() {
-                    Some(ui.min_rect())
+                    Some((ui.min_rect(), ui.clip_rect()))
                 } else {
                     None
                 }
             })
             .flatten();
-        if let Some(rect) = temp_rect {
-            self._ui().advance_cursor_after_rect(rect);
+        if let Some((rect, clip_rect)) = temp_rect {
+            self.finish_cell(rect, clip_rect);
         }
         self.temp_ui = None;
     }
+    /// Advance cursor of the parent ui after cell content
+    fn finish_cell(&mut self, mut rect: Rect, clip_rect: Rect) {
+        let (min, max) = self.state.width_limits(self.state.column.saturating_sub(1));
+        if let ExUiMode::Grid {} = self.state.mode {
+            // aligned content is measured as if it was aligned to the left,
+            // otherwise the column would keep the width it was aligned in
+            let left = self.ui.available_rect_before_wrap().left();
+            rect = rect.translate(vec2(left - rect.left(), 0.0));
+            rect.max.x = rect.max.x.max(rect.min.x + min).min(rect.min.x + max);
+            // row partially revealed by animation takes only its visible height
+            rect.max.y = rect.max.y.max(rect.min.y + self.state.min_row_height);
+            if let Some(limit) = self.state.reveal_limit() {
+                rect.max.y = rect.max.y.min(limit).max(rect.min.y);
+            }
+        } else if max.is_finite() {
+            // clipped content of the cell does not take space in the row
+            rect.max.x = rect.max.x.min(clip_rect.right());
+        }
+        if let Some(ref mut header) = self.state.sticky_header {
+            header.rect = header.rect.union(rect);
+            rect = rect.translate(vec2(0.0, -header.offset));
+        }
+        self.measure_cell(rect);
+        self._ui().advance_cursor_after_rect(rect);
+    }
+    /// Separators between columns (Traditional mode) that change width of the column on the left when dragged,
+    /// double-click makes column fit its content
+    pub(crate) fn column_resize_handles(&mut self) {
+        if !matches!(self.state.mode, ExUiMode::Grid {}) {
+            return;
+        }
+        let grid_rect = self.ui.min_rect();
+        let spacing = self.ui.spacing().item_spacing.x;
+        let grab_radius = self.ui.style().interaction.resize_grab_radius_side;
+        for column in 0..self.state.col_right.len() {
+            // egui::Grid makes column at least `min_col_width` wide, even if its content is narrower
+            let content_width = self.state.col_widths[column];
+            let left = self.state.col_right[column] - content_width;
+            let x = left + content_width.max(self.state.min_col_width) + 0.5 * spacing;
+            if !x.is_finite() {
+                continue;
+            }
+            let rect =
+                Rect::from_x_y_ranges(x - grab_radius..=x + grab_radius, grid_rect.y_range());
+            let id = self.ui.id().with(("column_resize", column));
+            let response = self.ui.interact(rect, id, Sense::click_and_drag());
+            let spec = self.state.columns.get(column);
+            let min_width = spec.and_then(|spec| spec.min);
+            let min_width = min_width.unwrap_or(0.0).max(self.state.min_col_width);
+            let max_width = spec.and_then(|spec| spec.max).unwrap_or(f32::INFINITY);
+            let widths = &mut self.state.column_widths;
+            if widths.len() <= column {
+                widths.resize(column + 1, None);
+            }
+            if response.double_clicked() {
+                widths[column] = None;
+            } else if response.dragged() {
+                let width = widths[column].unwrap_or(content_width);
+                let width = (width + response.drag_delta().x).min(max_width);
+                widths[column] = Some(width.max(min_width));
+            }
+            let stroke = if response.dragged() {
+                self.ui.visuals().widgets.active.bg_stroke
+            } else if response.hovered() {
+                self.ui.visuals().widgets.hovered.bg_stroke
+            } else {
+                continue;
+            };
+            self.ui.ctx().set_cursor_icon(CursorIcon::ResizeColumn);
+            let painter = self.ui.painter();
+            painter.vline(x, grid_rect.y_range(), stroke);
+        }
+        // trailing `None`s are not needed
+        let widths = &mut self.state.column_widths;
+        while widths.last() == Some(&None) {
+            widths.pop();
+        }
+    }
+    /// Report calls that were not balanced in this frame (see [`Validation`])
+    pub(crate) fn finish_validation(&mut self) {
+        // mismatches are reported at rows where unmatched calls were made
+        let validation = &mut self.state.validation;
+        for cursor in std::mem::take(&mut validation.collapsing) {
+            validation.error("start_collapsing not matched by stop_collapsing", &cursor);
+        }
+        if let Some(cursor) = validation.keep_cell.take() {
+            validation.error("keep_cell_start not matched by keep_cell_stop", &cursor);
+        }
+        for cursor in std::mem::take(&mut validation.disabled) {
+            validation.error("start_disabled not matched by stop_disabled", &cursor);
+        }
+        self.state.validation.report(&self.ui);
+    }
+    /// Record mismatched call at the current row (see [`Validation`])
+    fn validation_error(&mut self, error: &str) {
+        let state = self.state.as_mut();
+        state.validation.error(error, &state.row_cursor);
+    }
+    /// Save layout measurements of this frame, so they can be used in the next one
+    pub(crate) fn store_memory(&mut self, id: Id) {
+        let memory = ExUiMemory {
+            width_max: self.state.width_max,
+            row_heights: std::mem::take(&mut self.state.row_heights),
+            first_row: self.state.first_row,
+            size: self.ui.min_rect().size(),
+            row_rects: std::mem::take(&mut self.state.row_rects),
+            col_widths: self.state.col_widths.clone(),
+        };
+        self.data_mut(|d| d.insert_temp(id, memory));
+    }
+    /// Record size of the current cell (Traditional mode only)
+    fn measure_cell(&mut self, rect: Rect) {
+        if let ExUiMode::Grid {} = self.state.mode {
+            let ExUiInner {
+                column,
+                row,
+                first_row,
+                col_widths,
+                col_right,
+                row_heights,
+                row_rect,
+                ..
+            } = self.state.as_mut();
+            *row_rect = row_rect.union(rect);
+            let column = column.saturating_sub(1);
+            if col_widths.len() <= column {
+                col_widths.resize(column + 1, 0.0);
+                col_right.resize(column + 1, f32::NEG_INFINITY);
+            }
+            col_widths[column] = col_widths[column].max(rect.width());
+            col_right[column] = col_right[column].max(rect.right());
+            let row = *row - *first_row;
+            if row_heights.len() <= row {
+                row_heights.resize(row + 1, 0.0);
+            }
+            row_heights[row] = row_heights[row].max(rect.height());
+        }
+    }
+}
+/// Byte ranges of `text` that (case insensitively) match `query` (which is already lowercase, see [`to_lowercase`])
+fn find_matches(text: &str, query: &str) -> Vec<std::ops::Range<usize>> {
+    let query: Vec<char> = query.chars().collect();
+    let chars: Vec<(usize, char)> = text
+        .char_indices()
+        .map(|(i, c)| (i, lowercase(c)))
+        .collect();
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while !query.is_empty() && i + query.len() <= chars.len() {
+        if chars[i..i + query.len()]
+            .iter()
+            .map(|(_, c)| *c)
+            .eq(query.iter().copied())
+        {
+            let end = chars
+                .get(i + query.len())
+                .map_or(text.len(), |(end, _)| *end);
+            matches.push(chars[i].0..end);
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    matches
+}
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+/// Lowercase `text` in a way that keeps number of chars (required by [`find_matches`])
+pub(crate) fn to_lowercase(text: &str) -> String {
+    text.chars().map(lowercase).collect()
+}
+/// Layout `text` with background of `matches` highlighted
+fn highlight_matches(
+    text: RichText,
+    matches: &[std::ops::Range<usize>],
+    style: &Style,
+) -> text::LayoutJob {
+    let mut job = text::LayoutJob::default();
+    text.append_to(&mut job, style, FontSelection::Default, Align::Center);
+    let format = job.sections.pop().map(|s| s.format).unwrap_or_default();
+    let mut highlighted = format.clone();
+    highlighted.background = style.visuals.selection.bg_fill;
+    let mut section = |byte_range: std::ops::Range<usize>, format: &TextFormat| {
+        if !byte_range.is_empty() {
+            job.sections.push(text::LayoutSection {
+                leading_space: 0.0,
+                byte_range,
+                format: format.clone(),
+            });
+        }
+    };
+    let mut start = 0;
+    for range in matches {
+        section(start..range.start, &format);
+        section(range.clone(), &highlighted);
+        start = range.end;
+    }
+    section(start..job.text.len(), &format);
+    job
+}
+/// In compact mode value of the cell is preceded by name of its column (if it is known)
+fn column_label(ui: &mut Ui, column_names: &[String], column: usize) {
+    if let Some(name) = column_names.get(column - 1).filter(|name| !name.is_empty()) {
+        ui.label(RichText::new(format!("{name}:")).weak());
+    }
+}
+/// Sub-ui for the cell in `CompactLabels::KeyValue` layout, value is preceded by its label (aligned in the column)
+fn key_value_ui(
+    ui: &mut Ui,
+    column_names: &[String],
+    column: usize,
+    label_width: f32,
+    id_salt: Id,
+) -> Ui {
+    let max_rect = ui.available_rect_before_wrap();
+    let mut child = cell_child_ui(ui, max_rect, cell_layout(Align::Min), id_salt);
+    let name = column_names
+        .get(column - 1)
+        .map_or("", |name| name.as_str());
+    let size = vec2(label_width, child.spacing().interact_size.y);
+    child.allocate_ui_with_layout(size, Layout::right_to_left(Align::Center), |ui| {
+        ui.set_min_width(label_width);
+        if !name.is_empty() {
+            ui.label(RichText::new(format!("{name}:")).weak());
+        }
+    });
+    child
 }
 fn disable_ui<'a: 'd, 'b: 'd, 'c: 'd, 'd>(
     temp_ui: &'c mut Option<MaybeOwnedMut<'a, Ui>>,
@@ -163,7 +905,17 @@ impl<'a, 'b> DerefMut for ExUi<'a, 'b> {
             return u;
         }
         self.advance_temp_rect();
+        self.state.row_disabled |= self.state.disabled != 0;
         let id = self.id();
+        if self.state.column == 0 && self.keep_cell.is_none() {
+            self.begin_row();
+        }
+        let header_collapsed = self.state.collapsing_header && self.header_collapsed(id);
+        let openness = match self.state.collapsing_header {
+            true => self.header_openness(id),
+            false => 0.0,
+        };
+        let persist = self.state.persist_collapsed;
         if let Some(ExUiKeepCell {
             ui,
             widgets_in_cell,
@@ -174,14 +926,32 @@ impl<'a, 'b> DerefMut for ExUi<'a, 'b> {
             disable_ui(&mut self.temp_ui, ui, self.state.disabled)
         } else {
             self.state.column += 1;
+            let row_height = self.state.row_height_prev();
+            let column_index = self.state.column - 1;
+            let (min_width, max_width) = self.state.width_limits(column_index);
+            let reveal_limit = self.state.reveal_limit();
             let ExUiInner {
                 column,
                 collapsing_header,
                 row_cursor,
                 mode,
                 disabled,
+                header,
+                sticky_header,
+                column_names,
+                compact_labels,
+                label_width,
+                columns,
+                col_widths_prev,
+                min_col_width,
+                expand_buttons: with_expand_buttons,
+                grid_id,
+                collapse_icon,
+                indent_width,
                 ..
             } = self.state.as_mut();
+            let spec = columns.get(column_index);
+            let column_names: &[String] = if *header { &[] } else { column_names };
 
             if let ExUiMode::Compact {
                 ref mut ui_row,
@@ -190,15 +960,24 @@ impl<'a, 'b> DerefMut for ExUi<'a, 'b> {
             {
                 match column {
                     1 => {
-                        let mut ui = simpleui(ui_row.last_mut().unwrap().ui());
-                        if *collapsing_header {
-                            let collapsed =
-                                self.ui.data_mut(|d| d.get_temp_mut_or(id, false).clone());
-                            let icon = if collapsed { "⏵" } else { "⏷" };
-                            if ui.add(Button::new(icon).frame(false).small()).clicked() {
-                                self.ui.data_mut(|d| d.insert_temp(id, !collapsed));
-                            }
+                        let ui = ui_row.last_mut().unwrap().ui();
+                        let max_rect = ui.available_rect_before_wrap();
+                        let layout = Layout::left_to_right(Default::default());
+                        let mut ui = cell_child_ui(ui, max_rect, layout, id.with(*column));
+                        if *collapsing_header
+                            && collapse_toggle(&mut ui, header_collapsed, openness, *collapse_icon)
+                        {
+                            store_collapsed(ui.ctx(), id, !header_collapsed, persist);
                         };
+                        if *header && *with_expand_buttons {
+                            expand_buttons(&mut ui, *grid_id);
+                        }
+                        if *compact_labels != CompactLabels::None {
+                            column_label(&mut ui, column_names, *column);
+                        }
+                        if let Some(spec) = spec {
+                            compact_column_spec(&mut ui, spec);
+                        }
                         if *disabled != 0 {
                             ui.disable();
                         }
@@ -210,15 +989,18 @@ impl<'a, 'b> DerefMut for ExUi<'a, 'b> {
                         let indent = ui.spacing().indent;
                         let mut child_rect = ui.available_rect_before_wrap();
                         child_rect.min.x += indent;
+                        let layout = if *compact_labels == CompactLabels::KeyValue {
+                            Layout::top_down(Align::Min)
+                        } else {
+                            Layout::left_to_right(Align::TOP).with_main_wrap(true)
+                        };
 
                         #[cfg(feature = "egui29")]
                         {
                             *ui_columns = Some(ui.new_child(UiBuilder {
-                                id_salt: Some("indent".into()),
+                                id_salt: Some(id.with("indent")),
                                 max_rect: Some(child_rect),
-                                layout: Some(
-                                    Layout::left_to_right(Align::TOP).with_main_wrap(true),
-                                ),
+                                layout: Some(layout),
                                 ..Default::default()
                             }));
                         }
@@ -226,70 +1008,132 @@ impl<'a, 'b> DerefMut for ExUi<'a, 'b> {
                         {
                             *ui_columns = Some(ui.child_ui_with_id_source(
                                 child_rect,
-                                Layout::left_to_right(Align::TOP).with_main_wrap(true),
-                                "indent",
+                                layout,
+                                id.with("indent"),
                                 #[cfg(feature = "egui28")]
                                 None,
                             ));
                         }
-
-                        return disable_ui(
-                            &mut self.temp_ui,
-                            ui_columns.as_mut().unwrap(),
-                            *disabled,
-                        );
                     }
                     _ => {
-                        if let Some(ref mut col) = ui_columns {
-                            col.separator();
-                            return disable_ui(&mut self.temp_ui, col, *disabled);
-                        } else {
-                            unreachable!()
+                        if *compact_labels != CompactLabels::KeyValue {
+                            ui_columns.as_mut().unwrap().separator();
                         }
                     }
                 }
-            } else {
-                if *collapsing_header && *column == 1 {
-                    let mut ui = simpleui(self.ui.as_mut());
-                    for _ in 0..row_cursor.len() - 2 {
-                        ui.separator();
+                let col = ui_columns.as_mut().unwrap();
+                if *compact_labels == CompactLabels::KeyValue {
+                    let label_width = match label_width {
+                        Some(width) => *width,
+                        None if column_names.is_empty() => 0.0,
+                        None => {
+                            let font_id = TextStyle::Body.resolve(col.style());
+                            let color = col.visuals().weak_text_color();
+                            let width = column_names
+                                .iter()
+                                .map(|name| {
+                                    let text = format!("{name}:");
+                                    let galley = col
+                                        .fonts(|f| f.layout_no_wrap(text, font_id.clone(), color));
+                                    galley.size().x
+                                })
+                                .fold(0.0, f32::max);
+                            *label_width.insert(width)
+                        }
+                    };
+                    let cell_id = id.with(*column);
+                    let mut ui = key_value_ui(col, column_names, *column, label_width, cell_id);
+                    if let Some(spec) = spec {
+                        compact_column_spec(&mut ui, spec);
                     }
-                    let collapsed = ui.data_mut(|d| d.get_temp_mut_or(id, false).clone());
-                    let icon = if collapsed { "⏵" } else { "⏷" };
-                    if ui.add(Button::new(icon).frame(false).small()).clicked() {
-                        ui.data_mut(|d| d.insert_temp(id, !collapsed));
+                    if *disabled != 0 {
+                        ui.disable();
                     }
+                    self.temp_ui = Some(MaybeOwnedMut::Owned(ui));
+                    return self.temp_ui.as_mut().unwrap();
+                }
+                if *compact_labels == CompactLabels::Inline {
+                    column_label(col, column_names, *column);
+                }
+                if let Some(spec) = spec.filter(|spec| spec.min.is_some() || spec.max.is_some()) {
+                    let mut ui = simpleui(col);
+                    compact_column_spec(&mut ui, spec);
                     if *disabled != 0 {
                         ui.disable();
                     }
                     self.temp_ui = Some(MaybeOwnedMut::Owned(ui));
                     return self.temp_ui.as_mut().unwrap();
-                } else if row_cursor.len() > 1 {
+                }
+                return disable_ui(&mut self.temp_ui, col, *disabled);
+            } else {
+                let align = spec.map_or(Align::Min, |spec| spec.align);
+                let mut max_rect = grid_cell_rect(self.ui.as_mut(), row_height);
+                if max_width.is_finite() {
+                    max_rect.max.x = max_rect.min.x + max_width;
+                } else if let (false, Some(width)) =
+                    (align == Align::Min, col_widths_prev.get(column_index))
+                {
+                    // aligned content needs width of the column, not the space available for it
+                    let width = width.max(*min_col_width).max(min_width);
+                    max_rect.max.x = max_rect.min.x + width;
+                }
+                // every cell is placed in its own sub-ui, so that its size can be measured,
+                // it is identified by the row, so that memory of its widgets follows keyed rows
+                let mut ui = match sticky_header {
+                    Some(header) => header.cell_ui(self.ui.as_mut(), max_rect, *column, align),
+                    None => grid_cell_ui(self.ui.as_mut(), max_rect, align, id.with(*column)),
+                };
+                if max_width.is_finite() {
+                    // content that does not fit in the column is clipped
+                    let mut clip_rect = ui.clip_rect();
+                    clip_rect.max.x = clip_rect.max.x.min(max_rect.right());
+                    ui.set_clip_rect(clip_rect);
+                }
+                if let Some(limit) = reveal_limit {
+                    let mut clip_rect = ui.clip_rect();
+                    clip_rect.max.y = clip_rect.max.y.min(limit);
+                    ui.set_clip_rect(clip_rect);
+                }
+                if spec.is_some_and(|spec| spec.wrap) {
+                    wrap_text(&mut ui);
+                }
+                if *column == 1 && row_cursor.len() > 1 {
                     //if rows are collapsed, we should not reach here(reaching here should be stopped by `collapsing_rows_body`)
-                    if *column == 1 {
-                        let mut ui = simpleui(self.ui.as_mut());
-                        for _ in 0..row_cursor.len() - 1 {
-                            ui.separator();
-                        }
-                        if *disabled != 0 {
-                            ui.disable();
-                        }
-                        self.temp_ui = Some(MaybeOwnedMut::Owned(ui));
-                        return self.temp_ui.as_mut().unwrap();
-                    } else {
-                        return disable_ui(&mut self.temp_ui, self.ui.as_mut(), *disabled);
+                    // tree guides are painted in the space left here (see `ExUi::tree_guide_elbow`)
+                    let indent = row_cursor.len() - 1 - *collapsing_header as usize;
+                    ui.add_space(indent as f32 * *indent_width);
+                    if *collapsing_header
+                        && collapse_toggle(&mut ui, header_collapsed, openness, *collapse_icon)
+                    {
+                        store_collapsed(ui.ctx(), id, !header_collapsed, persist);
                     }
-                } else {
-                    return disable_ui(&mut self.temp_ui, self.ui.as_mut(), *disabled);
                 }
+                if *header && *column == 1 && *with_expand_buttons {
+                    expand_buttons(&mut ui, *grid_id);
+                }
+                if *disabled != 0 {
+                    ui.disable();
+                }
+                self.temp_ui = Some(MaybeOwnedMut::Owned(ui));
+                return self.temp_ui.as_mut().unwrap();
             };
         }
     }
 }
 impl<'a, 'b> From<&'a mut Ui> for ExUi<'a, 'b> {
     fn from(ui: &'a mut Ui) -> Self {
-        let mut inner: ExUiInner = Default::default();
-        inner.width_max_prev = ui.data_mut(|d| d.get_temp_mut_or(ui.id(), 0.0).clone());
+        let memory: ExUiMemory = ui.data_mut(|d| d.get_temp(ui.id())).unwrap_or_default();
+        let inner = ExUiInner {
+            width_max_prev: memory.width_max,
+            row_heights_prev: memory.row_heights,
+            first_row_prev: memory.first_row,
+            size_prev: memory.size,
+            row_rects_prev: memory.row_rects,
+            col_widths_prev: memory.col_widths,
+            min_row_height: ui.spacing().interact_size.y,
+            grid_id: ui.id(),
+            ..Default::default()
+        };
         ExUi {
             ui: MaybeOwnedMut::Borrowed(ui),
             state: MaybeOwnedMut::Owned(inner),
@@ -315,112 +1159,270 @@ impl<'a, 'b> ExUi<'a, 'b> {
     pub fn empty_row(&mut self) {
         self.end_row();
         self.state.column = 1;
+        if let (ExUiMode::Grid {}, false) = (&self.state.mode, self.collapsed()) {
+            // minimal height of the row is kept by its cells (see `Self::finish_cell`), not by `egui::Grid`
+            let rect = Rect::from_min_size(self.ui.cursor().min, Vec2::ZERO);
+            let clip_rect = self.ui.clip_rect();
+            self.finish_cell(rect, clip_rect);
+        }
         self.end_row();
     }
 
     /// Move to the next row.
     /// No-op if we are already at the beginning of the new row.
-    pub fn end_row(&mut self) {
-        self.keep_cell_stop();
+    ///
+    /// Returns response of the whole row (see [`RowResponse`]).
+    pub fn end_row(&mut self) -> RowResponse {
+        let row_hidden = self.collapsed();
+        self.end_row_inner(row_hidden)
+    }
+
+    fn end_row_inner(&mut self, row_hidden: bool) -> RowResponse {
+        if self
+            .keep_cell
+            .as_ref()
+            .is_some_and(|cell| cell.nesting_count != 0)
+        {
+            self.validation_error("keep_cell_start not matched by keep_cell_stop before end_row");
+        }
+        self.keep_cell_end();
         self.advance_temp_rect();
+        let id = self.id();
+        self.record_match(id);
+        let path = self.row_path();
+        let mut row_rect = self.state.row_rect;
+        let row_ended = self.state.column != 0 && !row_hidden && !self.state.header;
+        let depth = self.state.row_cursor.len() - 1 - self.state.collapsing_header as usize;
+        let mut body_openness = None;
         if self.state.collapsing_header {
             self.state.collapsing_header = false;
-            let id = self.id();
-            if !self.collapsed() {
-                self.state
-                    .collapsed
-                    .push(self.ui.data_mut(|d| d.get_temp_mut_or(id, false).clone()));
+            if !self.level_collapsed() {
+                let row_response = self.state.row_response.as_ref();
+                if self.state.clickable_headers && row_response.is_some_and(|r| r.clicked()) {
+                    let collapsed = self.header_collapsed(id);
+                    store_collapsed(self.ctx(), id, !collapsed, self.state.persist_collapsed);
+                }
+                let openness = self.header_openness(id);
+                self.state.collapsed.push(openness == 0.0);
+                body_openness = Some(openness).filter(|openness| *openness > 0.0);
             }
         }
         if self.state.column != 0 {
             *self.state.row_cursor.last_mut().unwrap() += 1;
+            *self.state.row_keys.last_mut().unwrap() = None;
+            if !row_hidden {
+                self.state.row += 1;
+            }
         }
         let indent = self.state.row_cursor.len();
         let mut width_max = self.state.width_max;
         let width_max_prev = self.state.width_max_prev;
-        let collapsed = self.collapsed();
-        if let ExUiMode::Compact {
-            ref mut ui_row,
-            ref mut ui_columns,
-        } = self.state.mode
-        {
-            let mut rect_columns = ui_columns.as_ref().map_or(
-                ui_row
-                    .last_mut()
-                    .map_or(Rect::NOTHING, |fr| fr.content_ui.min_rect()),
-                |u| u.min_rect(),
-            );
-            *ui_columns = None;
-            width_max = width_max.max(rect_columns.max.x);
-            if ui_row.len() >= indent {
-                //indent kept at the same level
-                let mut row_popped = ui_row.pop().unwrap();
-                if !collapsed {
-                    row_popped.end(width_max.max(width_max_prev), rect_columns);
-                    rect_columns = row_popped.ui().min_rect();
-                }
+        let collapsed = self.level_collapsed();
+        let frame = self.state.compact_frame(indent - 1, self.ui.style());
+        let limit = self.state.reveal_limit();
+        match self.state.mode {
+            ExUiMode::Compact {
+                ref mut ui_columns, ..
+            } if row_hidden => {
+                // frame of the hidden row stays empty, so it is reused by the next row
+                *ui_columns = None;
             }
+            ExUiMode::Compact {
+                ref mut ui_row,
+                ref mut ui_columns,
+            } => {
+                let mut rect_columns = ui_columns.as_ref().map_or(
+                    ui_row
+                        .last_mut()
+                        .map_or(Rect::NOTHING, |fr| fr.content_ui.min_rect()),
+                    |u| u.min_rect(),
+                );
+                if let Some(run) = ui_row.last() {
+                    #[cfg(any(feature = "egui28", feature = "egui29"))]
+                    let rect =
+                        rect_columns.union(run.content_ui.min_rect()) + run.frame.inner_margin;
+                    #[cfg(not(any(feature = "egui28", feature = "egui29")))]
+                    let rect = run
+                        .frame
+                        .inner_margin
+                        .expand_rect(rect_columns.union(run.content_ui.min_rect()));
+                    row_rect = rect;
+                }
+                *ui_columns = None;
+                width_max = width_max.max(rect_columns.max.x);
+                if ui_row.len() >= indent {
+                    //indent kept at the same level
+                    let mut row_popped = ui_row.pop().unwrap();
+                    if !collapsed {
+                        row_popped.end(width_max.max(width_max_prev), rect_columns);
+                        rect_columns = row_popped.ui().min_rect();
+                    }
+                }
 
-            let ui = ui_row.last_mut().map_or(self.ui.borrow_mut(), |x| x.ui());
-            ui.advance_cursor_after_rect(rect_columns);
-            ui.end_row();
-            let fr = FrameRun::begin(Frame::group(ui.style()), indent, ui);
-            ui_row.push(fr);
-
-            //TODO add frame configuration
-        } else if self.state.column != 0 {
-            self.ui.end_row()
+                if let Some(limit) = limit {
+                    rect_columns.max.y = rect_columns.max.y.min(limit).max(rect_columns.min.y);
+                }
+                let ui = ui_row.last_mut().map_or(self.ui.borrow_mut(), |x| x.ui());
+                ui.advance_cursor_after_rect(rect_columns);
+                ui.end_row();
+                let fr = FrameRun::begin(frame, indent, ui);
+                ui_row.push(fr);
+            }
+            ExUiMode::Grid {} => {
+                if self.state.column != 0 && !row_hidden {
+                    let top = self.ui.cursor().top();
+                    self.ui.end_row();
+                    let bottom = self.ui.cursor().top() - self.ui.spacing().item_spacing.y;
+                    self.tree_guide_elbow(depth, top, bottom);
+                }
+            }
+        }
+        if let Some(openness) = body_openness {
+            self.begin_body(id, openness);
+        }
+        self.reveal_rows();
+        let response = if row_ended {
+            self.finish_row_response(id, path, row_rect)
+        } else {
+            self.dummy_response()
+        };
+        self.state.row_response = None;
+        self.state.row_disabled = false;
+        self.state.disabled_reason = None;
+        self.state.row_rect = Rect::NOTHING;
+        if let Some(header) = self.state.sticky_header.take() {
+            header.paint(&self.ui, self.state.size_prev.x);
+        }
+        if self.state.row_cursor.len() == 1 {
+            let top = self.ui.cursor().top();
+            let ExUiInner {
+                row_cursor,
+                row,
+                header,
+                row_blocks,
+                ..
+            } = self.state.as_mut();
+            if let Some(row_blocks) = row_blocks {
+                row_blocks.measure(row_cursor[0], top, *row, *header);
+            }
         }
+        self.state.header = false;
         self.state.column = 0;
         self.state.width_max = width_max;
+        RowResponse {
+            response,
+            hidden: row_hidden,
+        }
+    }
+
+    /// Response of the row `id` that has just ended (`rect` is the rect of its cells)
+    fn finish_row_response(&mut self, id: Id, path: Vec<usize>, mut rect: Rect) -> Response {
+        if let ExUiMode::Grid {} = self.state.mode {
+            // row spans whole grid width
+            let spacing = self.ui.spacing().item_spacing;
+            rect.min.x = self.ui.min_rect().left();
+            rect.max.x = rect.max.x.max(rect.min.x + self.state.size_prev.x);
+            rect = rect.expand2(0.5 * spacing);
+        }
+        let id = id.with("row");
+        self.state.row_rects.insert(id, rect);
+        // hover-only interaction does not take clicks from the cells
+        let hovered = self.ui.interact(rect, id.with("hover"), Sense::hover());
+        self.end_row_selection(path, rect);
+        let response = match self.state.row_response.take() {
+            Some(response) => response.union(hovered),
+            None => hovered,
+        };
+        // rows hidden by animation are not placed in the layer of the grid
+        if let (Some(body), true) = (
+            self.state.body_responses.last_mut(),
+            response.layer_id == self.ui.layer_id(),
+        ) {
+            *body = Some(match body.take() {
+                Some(body) => body.union(response.clone()),
+                None => response.clone(),
+            });
+        }
+        match self.state.disabled_reason.take() {
+            Some(reason) if self.state.row_disabled => response.on_hover_text(reason),
+            _ => response,
+        }
     }
 
     pub fn start_collapsing(&mut self) {
         if !self.state.collapsing_header {
             self.state.collapsing_header = true;
             self.state.row_cursor.push(0);
+            self.state.row_keys.push(None);
+            let cursor = self.state.row_cursor.clone();
+            self.state.validation.collapsing.push(cursor);
         }
     }
 
     pub fn stop_collapsing(&mut self) {
+        // last row of the body is often left open, it has to end at its own nesting level (with its own id)
+        if self.state.column != 0 {
+            self.end_row();
+        }
         self.temp_ui = None;
         self.state.collapsing_header = false;
+        self.end_body();
+        let limit = self.state.reveal_limit();
         if self.state.row_cursor.len() > 1 {
+            self.state.validation.collapsing.pop();
+            // collapsing header of the rows being closed
+            let mut header = self.state.row_cursor.clone();
+            *header.last_mut().unwrap() = 0;
+            let header_filtered = self.filtered(self.cursor_id(&header));
             self.state.row_cursor.pop();
+            self.state.row_keys.pop();
             let len = self.state.row_cursor.len();
             self.state.collapsed.truncate(len);
             let mut width_max = self.state.width_max;
             let width_max_prev = self.state.width_max_prev;
-            let collapsed = self.collapsed();
+            let header_hidden = self.level_collapsed() || header_filtered;
             *self.state.row_cursor.last_mut().unwrap() += 1;
+            *self.state.row_keys.last_mut().unwrap() = None;
 
-            if let ExUiMode::Compact {
-                ref mut ui_row,
-                ref mut ui_columns,
-            } = self.state.mode
-            {
-                let rect_columns = ui_columns.as_ref().map_or(
+            match self.state.mode {
+                ExUiMode::Compact {
+                    ref mut ui_row,
+                    ref mut ui_columns,
+                } if !header_hidden => {
+                    let rect_columns = ui_columns.as_ref().map_or(
+                        ui_row
+                            .last_mut()
+                            .map_or(Rect::NOTHING, |fr| fr.content_ui.min_rect()),
+                        |u| u.min_rect(),
+                    );
+                    width_max = width_max.max(rect_columns.max.x);
+                    let mut row_popped = ui_row.pop().unwrap();
+                    row_popped.end(width_max.max(width_max_prev), rect_columns);
+                    let mut rect = row_popped.ui().min_rect();
+                    if let Some(limit) = limit {
+                        rect.max.y = rect.max.y.min(limit).max(rect.min.y);
+                    }
                     ui_row
                         .last_mut()
-                        .map_or(Rect::NOTHING, |fr| fr.content_ui.min_rect()),
-                    |u| u.min_rect(),
-                );
-                width_max = width_max.max(rect_columns.max.x);
-                let mut row_popped = ui_row.pop().unwrap();
-                if !collapsed {
-                    row_popped.end(width_max.max(width_max_prev), rect_columns);
+                        .map(|u| u.ui().advance_cursor_after_rect(rect));
                 }
-                ui_row
-                    .last_mut()
-                    .map(|u| u.ui().advance_cursor_after_rect(row_popped.ui().min_rect()));
+                _ => {}
             }
             self.state.width_max = width_max;
+            // no row is open here, in Compact mode frame of the row with the header is ended (unless it is hidden)
+            self.end_row_inner(header_hidden);
+        } else {
+            self.validation_error("stop_collapsing without matching start_collapsing");
+            self.end_row();
         }
-        self.end_row();
     }
 
     pub fn add_ex_opt<R>(&mut self, add_contents: impl FnOnce(&mut Ui) -> R) -> Option<R> {
         if self.collapsed() {
+            // cell is still counted, as hidden rows (eg. filtered out) have to keep their ids
+            if self.keep_cell.is_none() {
+                self.state.column += 1;
+            }
             None
         } else {
             Some(add_contents(self))
@@ -428,42 +1430,577 @@ impl<'a, 'b> ExUi<'a, 'b> {
     }
 
     pub fn collapsed(&self) -> bool {
+        self.level_collapsed() || self.row_filtered()
+    }
+
+    /// Rows at current nesting level are collapsed
+    fn level_collapsed(&self) -> bool {
         Some(true) == self.state.collapsed.last().copied()
     }
 
-    /// Adds collapsible rows header
-    pub fn collapsing_rows(
-        &mut self,
-        header_row: impl FnOnce(&mut ExUi) -> Response,
-    ) -> CollapsingRows<'a, 'b, '_> {
-        self.maybe_collapsing_rows(true, header_row)
+    /// Current row is hidden by filter
+    fn row_filtered(&self) -> bool {
+        !self.state.header && self.filtered(self.id())
     }
-    /// If `collapsible` == true, adds collapsible rows header,
-    /// otherwise simply adds `header_row` (without collapse/uncollapse button)
-    pub fn maybe_collapsing_rows(
-        &mut self,
-        collapsible: bool,
-        header_row: impl FnOnce(&mut ExUi) -> Response,
-    ) -> CollapsingRows<'a, 'b, '_> {
-        if collapsible {
-            self.start_collapsing()
-        }
-        CollapsingRows {
-            header_response: header_row(self),
+
+    /// Row `id` is hidden by filter
+    fn filtered(&self, id: Id) -> bool {
+        let filter = self.state.filter.as_ref();
+        filter.is_some_and(|filter| !filter.shown.contains(&id))
+    }
+
+    /// Subrows of collapsing header `id` are collapsed (filtering overrides state stored in memory)
+    fn header_collapsed(&mut self, id: Id) -> bool {
+        let persist = self.state.persist_collapsed;
+        let depth = self.state.row_cursor.len().saturating_sub(2);
+        let state = self.state.as_mut();
+        let request = state.collapse_request.as_mut();
+        match state.filter {
+            Some(ref filter) => !filter.open.contains(&id),
+            None => match request.and_then(|request| request.apply(id, depth)) {
+                Some(collapsed) => {
+                    store_collapsed(self.ctx(), id, collapsed, persist);
+                    collapsed
+                }
+                // default is not stored, so it can be still set with `CollapsingRows::initial_state`
+                None if persist => self.ui.data_mut(|d| d.get_persisted(id).unwrap_or(false)),
+                None => self.ui.data_mut(|d| d.get_temp(id).unwrap_or(false)),
+            },
+        }
+    }
+
+    /// How much subrows of collapsing header `id` are expanded (0.0 - collapsed, 1.0 - expanded),
+    /// in between while expanding/collapsing is animated (see [`ExGrid::animate`])
+    fn header_openness(&mut self, id: Id) -> f32 {
+        let collapsed = self.header_collapsed(id);
+        if self.state.animate && self.state.filter.is_none() {
+            self.ctx().animate_bool(id.with("openness"), !collapsed)
+        } else {
+            !collapsed as usize as f32
+        }
+    }
+
+    /// Position where the next row will be placed
+    fn next_row_top(&self) -> f32 {
+        match self.state.mode {
+            ExUiMode::Compact { ref ui_row, .. } => ui_row
+                .last()
+                .map_or(self.ui.cursor().top(), |fr| fr.content_ui.max_rect().top()),
+            ExUiMode::Grid {} => self.ui.cursor().top(),
+        }
+    }
+
+    /// Subrows of collapsing header `id` will be added; while they are animated,
+    /// only part of them (proportional to `openness`) is shown
+    fn begin_body(&mut self, id: Id, openness: f32) {
+        let top = self.next_row_top();
+        let height: Option<f32> = self.ui.data(|d| d.get_temp(id.with("body_height")));
+        // height of the body is known only after it was shown expanded,
+        // so first expanding of the rows is not animated
+        let limit = height
+            .filter(|_| openness < 1.0)
+            .map(|height| top + openness * height);
+        let level = self.state.collapsed.len() - 1;
+        let body = RowsBody {
+            id,
+            level,
+            top,
+            limit,
+            guide_end: None,
+        };
+        self.state.bodies.push(body);
+    }
+
+    /// Subrows of the innermost collapsing header were added, fully expanded body is measured
+    fn end_body(&mut self) {
+        let level = self.state.row_cursor.len() - 1;
+        if self
+            .state
+            .bodies
+            .last()
+            .is_some_and(|body| body.level == level)
+        {
+            let limit = self.state.reveal_limit();
+            let body = self.state.bodies.pop().unwrap();
+            self.paint_tree_guide(&body, limit);
+            if body.limit.is_none() {
+                let height = self.next_row_top() - body.top;
+                self.ui
+                    .data_mut(|d| d.insert_temp(body.id.with("body_height"), height));
+            }
+        }
+    }
+
+    /// Hides rows that would be placed below position revealed by animated bodies,
+    /// clips row that is revealed only partially
+    fn reveal_rows(&mut self) {
+        let top = self.next_row_top();
+        let bodies = &mut self.state.bodies;
+        if let Some(hidden) = bodies
+            .iter()
+            .position(|b| b.limit.is_some_and(|l| l <= top))
+        {
+            let level = bodies[hidden].level;
+            let limit = bodies[hidden].limit;
+            for body in bodies.split_off(hidden + 1) {
+                self.paint_tree_guide(&body, limit);
+            }
+            self.state.collapsed.truncate(level + 1);
+            self.state.collapsed[level] = true;
+        }
+        let limit = self.state.reveal_limit();
+        if let (Some(limit), ExUiMode::Compact { ui_row, .. }) = (limit, &mut self.state.mode) {
+            if let Some(fr) = ui_row.last_mut() {
+                let mut clip_rect = fr.content_ui.clip_rect();
+                clip_rect.max.y = clip_rect.max.y.min(limit);
+                fr.content_ui.set_clip_rect(clip_rect);
+            }
+        }
+    }
+
+    /// Position of vertical line of tree guide connecting subrows of nesting `level` (Traditional mode)
+    fn tree_guide_x(&self, level: usize) -> f32 {
+        let left = self.ui.min_rect().left();
+        left + (level - 1) as f32 * self.state.indent_width + 0.5 * self.ui.spacing().icon_width
+    }
+
+    /// Paints elbow of the tree guide of the row at `depth`, spanning `top`..`bottom` (Traditional mode)
+    fn tree_guide_elbow(&mut self, depth: usize, top: f32, bottom: f32) {
+        let level = self.state.bodies.last().map(|body| body.level);
+        if depth == 0 || level != Some(depth) {
+            return;
+        }
+        let x = self.tree_guide_x(depth);
+        let y = 0.5 * (top + bottom);
+        let end = self.ui.min_rect().left() + depth as f32 * self.state.indent_width;
+        let stroke = self.ui.visuals().widgets.noninteractive.bg_stroke;
+        let mut painter = self.ui.painter().clone();
+        if let Some(limit) = self.state.reveal_limit() {
+            let mut clip_rect = painter.clip_rect();
+            clip_rect.max.y = clip_rect.max.y.min(limit);
+            painter.set_clip_rect(clip_rect);
+        }
+        painter.line_segment([pos2(x, y), pos2(end, y)], stroke);
+        self.state.bodies.last_mut().unwrap().guide_end = Some(y);
+    }
+
+    /// Paints vertical line of tree guide from header of the `body` to elbow of its last subrow (Traditional mode)
+    fn paint_tree_guide(&self, body: &RowsBody, limit: Option<f32>) {
+        let Some(end) = body.guide_end else {
+            return;
+        };
+        let x = self.tree_guide_x(body.level);
+        let top = body.top - self.ui.spacing().item_spacing.y;
+        let stroke = self.ui.visuals().widgets.noninteractive.bg_stroke;
+        let mut painter = self.ui.painter().clone();
+        if let Some(limit) = limit {
+            let mut clip_rect = painter.clip_rect();
+            clip_rect.max.y = clip_rect.max.y.min(limit);
+            painter.set_clip_rect(clip_rect);
+        }
+        painter.line_segment([pos2(x, top), pos2(x, end)], stroke);
+    }
+
+    /// If current row (`id`) matches filter query, it and its collapsing headers will be shown
+    fn record_match(&mut self, id: Id) {
+        if !self.state.filter.as_ref().is_some_and(|f| f.row_matched) {
+            return;
+        }
+        let row_cursor = &self.state.row_cursor;
+        let headers: Vec<Id> = (1..row_cursor.len())
+            .map(|level| [&row_cursor[..level], &[0]].concat())
+            .filter(|header| header != row_cursor)
+            .map(|header| self.cursor_id(&header))
+            .collect();
+        let filter = self.state.filter.as_mut().unwrap();
+        filter.row_matched = false;
+        filter.shown_next.insert(id);
+        filter.shown_next.extend(headers.iter().copied());
+        filter.open_next.extend(headers);
+    }
+
+    /// Path of the current row: its nesting cursor (see [`Self::get_nesting_cursor`]),
+    /// with top level row identified by its index in data if rows are sorted (see [`Self::sort_order`])
+    fn row_path(&self) -> Vec<usize> {
+        let mut path = self.state.row_cursor.clone();
+        let sort_order = self.state.sort_order.as_ref();
+        if let Some(&row) = sort_order.and_then(|order| order.get(path[0])) {
+            path[0] = row;
+        }
+        path
+    }
+
+    /// Current row is selected (see [`ExGrid::selection`])
+    pub fn row_selected(&self) -> bool {
+        let selection = self.state.selection.as_ref();
+        selection.is_some_and(|selection| selection.selected.contains(&self.row_path()))
+    }
+
+    /// Makes current row interactive (before its cells are added, so they stay interactive)
+    fn begin_row(&mut self) {
+        if self.state.header {
+            return;
+        }
+        let id = self.id().with("row");
+        let rect = self.state.row_rects_prev.get(&id).copied();
+        // rows are Tab stops only when they can be selected
+        let sense = Sense {
+            focusable: self.state.selection.is_some(),
+            ..Sense::click()
+        };
+        let response = self.ui.interact(rect.unwrap_or(Rect::NOTHING), id, sense);
+        self.begin_row_selection(&response);
+        self.state.row_response = Some(response);
+    }
+
+    /// Unites `response` of the cell with response of the current row (see [`RowResponse`])
+    pub(crate) fn add_row_response(&mut self, response: &Response) {
+        if let Some(row_response) = self.state.row_response.take() {
+            self.state.row_response = Some(row_response.union(response.clone()));
+        }
+    }
+
+    /// Shows context menu when current row is right-clicked, in compact mode anywhere in the row frame.
+    /// Call it after cells of the row are added, clicks on cells added with [`ExUi`] methods (eg. [`Self::extext`]) open the menu,
+    /// while widgets added directly with [`Ui`] methods keep clicks they sense (eg. buttons, selectable labels) for themselves.
+    ///
+    /// It is the same as calling [`Response::context_menu`] on [`RowResponse`] returned by [`Self::end_row`].
+    pub fn row_context_menu(&mut self, add_contents: impl FnOnce(&mut Ui)) {
+        if self.state.column == 0 || self.collapsed() {
+            return;
+        }
+        if let Some(ref response) = self.state.row_response {
+            let _ = response.clone().context_menu(add_contents);
+        }
+    }
+
+    /// Registers click (or ArrowUp/ArrowDown press) on the current row and prepares its highlight
+    fn begin_row_selection(&mut self, response: &Response) {
+        if self.state.selection.is_none() {
+            return;
+        }
+        let path = self.row_path();
+        let selected = self.row_selected();
+        let selection = self.state.selection.as_mut().unwrap();
+        if selection.focus.as_ref() == Some(&path) {
+            selection.focus = None;
+            response.request_focus();
+        }
+        let modifiers = self.ui.input(|i| i.modifiers);
+        if response.clicked() {
+            response.request_focus();
+            selection.clicked = Some((path, modifiers));
+        } else if response.has_focus() {
+            // focus gained with Tab only moves focus, arrows move selection to the previous/next row
+            let (up, down) = self
+                .ui
+                .input(|i| (i.key_pressed(Key::ArrowUp), i.key_pressed(Key::ArrowDown)));
+            if up || down {
+                selection.key_move = Some((path, up, modifiers));
+            }
+            #[cfg(any(feature = "egui23", feature = "egui24", feature = "egui25"))]
+            let filter = EventFilter {
+                arrows: true,
+                ..Default::default()
+            };
+            #[cfg(not(any(feature = "egui23", feature = "egui24", feature = "egui25")))]
+            let filter = EventFilter {
+                vertical_arrows: true,
+                ..Default::default()
+            };
+            // arrows are not used by egui to move focus to other widgets
+            self.ui
+                .memory_mut(|m| m.set_focus_lock_filter(response.id, filter));
+        }
+        let fill = self.ui.visuals().selection.bg_fill;
+        match self.state.mode {
+            ExUiMode::Compact { ref mut ui_row, .. } => {
+                if let (true, Some(run)) = (selected, ui_row.last_mut()) {
+                    run.frame.fill = fill;
+                }
+            }
+            ExUiMode::Grid {} => {
+                let background = selected.then(|| self.ui.painter().add(Shape::Noop));
+                self.state.selection.as_mut().unwrap().background = background;
+            }
+        }
+    }
+
+    /// Records row that has just ended (and paints its highlight if it is selected)
+    fn end_row_selection(&mut self, path: Vec<usize>, rect: Rect) {
+        let fill = self.ui.visuals().selection.bg_fill;
+        let Some(ref mut selection) = self.state.selection else {
+            return;
+        };
+        if let Some(background) = selection.background.take() {
+            self.ui
+                .painter()
+                .set(background, Shape::rect_filled(rect, 0.0, fill));
+        }
+        selection.rows.push(path);
+    }
+
+    /// Identify the current row by `key` instead of its position (see [`Self::id`]),
+    /// so its collapse state and memory of its widgets follow it when other rows are inserted or removed
+    /// (memory of widgets with ids derived from the cell [`Ui`], eg. given `id_source`/`id_salt`;
+    /// automatically generated ids, eg. of `TextEdit` without `id_source`, still depend on position).
+    /// Rows nested in it are identified relatively to it.
+    /// Has to be called before cells of the row are added, `key` has to be unique within the grid.
+    pub fn row_key(&mut self, key: impl std::hash::Hash) {
+        let key = row_key_id(self.state.grid_id, key);
+        let level = self.state.row_keys.len() - 1;
+        // key of collapsing header identifies also rows nested in it
+        let level = match self.state.row_cursor.last() {
+            Some(0) if self.state.collapsing_header => level.saturating_sub(1),
+            _ => level,
+        };
+        self.state.row_keys[level] = Some(key);
+    }
+
+    /// Adds collapsible rows header
+    pub fn collapsing_rows(
+        &mut self,
+        header_row: impl FnOnce(&mut ExUi) -> Response,
+    ) -> CollapsingRows<'a, 'b, '_> {
+        self.maybe_collapsing_rows(true, header_row)
+    }
+    /// Same as [`Self::collapsing_rows`], but rows are identified by `key` (see [`Self::row_key`]),
+    /// so collapse state follows them when other rows are inserted or removed (see also [`ExGrid::persist_collapsed`]).
+    pub fn collapsing_rows_keyed(
+        &mut self,
+        key: impl std::hash::Hash,
+        header_row: impl FnOnce(&mut ExUi) -> Response,
+    ) -> CollapsingRows<'a, 'b, '_> {
+        self.row_key(key);
+        self.collapsing_rows(header_row)
+    }
+    /// If `collapsible` == true, adds collapsible rows header,
+    /// otherwise simply adds `header_row` (without collapse/uncollapse button)
+    pub fn maybe_collapsing_rows(
+        &mut self,
+        collapsible: bool,
+        header_row: impl FnOnce(&mut ExUi) -> Response,
+    ) -> CollapsingRows<'a, 'b, '_> {
+        if collapsible {
+            self.start_collapsing()
+        }
+        CollapsingRows {
+            header_response: header_row(self),
             exui: self,
             collapsible,
         }
     }
 
+    /// Adds row with cells added by `add_cells`, row is ended automatically (see [`Self::end_row`]).
+    /// Returns value returned by `add_cells` together with response of the row.
+    pub fn row<R>(&mut self, add_cells: impl FnOnce(&mut Self) -> R) -> RowInnerResponse<R> {
+        if self.state.column != 0 {
+            self.end_row();
+        }
+        let inner = add_cells(self);
+        RowInnerResponse {
+            inner,
+            response: self.end_row(),
+        }
+    }
+
+    /// Adds collapsible rows: header row with cells added by `add_header`
+    /// and subrows added by `add_body` (it is not called when subrows are collapsed).
+    /// Unlike [`Self::collapsing_rows`] (or [`Self::start_collapsing`]/[`Self::stop_collapsing`]) rows are always ended and nesting is always balanced.
+    ///
+    /// `header_response` of returned [`CollapsingResponse`] is response of the header row (see [`RowResponse`]),
+    /// `header_returned` is value returned by `add_header`.
+    pub fn collapsing<H, R>(
+        &mut self,
+        add_header: impl FnOnce(&mut Self) -> H,
+        add_body: impl FnOnce(&mut ExUi) -> R,
+    ) -> CollapsingResponse<R, H> {
+        if self.state.column != 0 {
+            self.end_row();
+        }
+        self.start_collapsing();
+        let header_returned = add_header(self);
+        let rows = CollapsingRows {
+            header_response: self.dummy_response(),
+            exui: self,
+            collapsible: true,
+        };
+        let (header_response, body, openness, toggled) = rows.add_body(|ui| {
+            let nesting = ui.state.row_cursor.len();
+            ui.state.body_responses.push(None);
+            let ret = add_body(ui);
+            // close rows left open by the body
+            if ui.state.column != 0 {
+                ui.end_row();
+            }
+            while ui.state.row_cursor.len() > nesting {
+                ui.stop_collapsing();
+            }
+            let body_response = ui.state.body_responses.pop().flatten();
+            // rows of the body are also rows of the outer body
+            if let (Some(outer), Some(body)) = (ui.state.body_responses.last_mut(), &body_response)
+            {
+                *outer = Some(match outer.take() {
+                    Some(outer) => outer.union(body.clone()),
+                    None => body.clone(),
+                });
+            }
+            let body_response = body_response.unwrap_or_else(|| ui.dummy_response());
+            (body_response, ret)
+        });
+        let (body_response, body_returned) = body.unzip();
+        CollapsingResponse {
+            header_response: header_response.response,
+            header_returned,
+            body_response,
+            body_returned,
+            openness,
+            toggled,
+        }
+    }
+
+    /// Adds header row (preferably as the first row of the grid).
+    ///
+    /// Header is painted with distinct background and
+    /// in Traditional mode it stays visible at the top of the [`ScrollArea`] when grid is scrolled.
+    /// In compact mode texts added to the header with [`Self::extext`] label values in each following row (eg. "Name: foo"),
+    /// they replace names set with [`ExGrid::column_names`] (see also [`ExGrid::compact_labels`]).
+    pub fn header_row<R>(&mut self, add_header: impl FnOnce(&mut Self) -> R) -> R {
+        if self.state.column != 0 {
+            self.end_row();
+        }
+        self.state.header = true;
+        self.state.column_names.clear();
+        self.state.label_width = None;
+        let fill = self.visuals().extreme_bg_color;
+        match self.state.mode {
+            ExUiMode::Compact { ref mut ui_row, .. } => {
+                ui_row.last_mut().unwrap().frame.fill = fill;
+            }
+            ExUiMode::Grid {} => {
+                let row_height = self.state.row_height_prev();
+                let header = StickyHeader::new(&self.ui, row_height, self.state.size_prev.y);
+                self.state.sticky_header = Some(header);
+            }
+        }
+        // header is not counted as a data row
+        let row_cursor = self.state.row_cursor.clone();
+        let ret = add_header(self);
+        self.end_row();
+        self.state.row_cursor = row_cursor;
+        ret
+    }
+
     /// In grid mode this is the same as `Self::label`, in compact mode it uses larger font in first column (currently `Self::heading`).
+    /// In header row (see [`Self::header_row`]) text is emphasized and stored as the column name.
     pub fn extext(&mut self, text: impl Into<RichText>) -> Response {
+        let mut text = text.into();
+        let mut matches = Vec::new();
+        if self.state.header {
+            let column = self.state.column;
+            let names = &mut self.state.column_names;
+            names.resize(names.len().max(column + 1), String::new());
+            names[column] = text.text().to_owned();
+            text = text.strong();
+        } else if let Some(ref mut filter) = self.state.filter {
+            matches = find_matches(text.text(), &filter.query);
+            filter.row_matched |= !matches.is_empty();
+        }
         if matches!(self.state.mode, ExUiMode::Compact { .. }) && self.state.column == 0 {
-            self.add_ex_opt(|ui| ui.heading(text))
+            text = text.heading();
+        }
+        let response = if matches.is_empty() {
+            self.add_ex_opt(|ui| ui.label(text))
         } else {
-            self.add_ex_opt(|ui| ui.label(text.into()))
+            self.add_ex_opt(|ui| {
+                let text = highlight_matches(text, &matches, ui.style());
+                ui.label(text)
+            })
+        };
+        match response {
+            Some(response) => {
+                self.add_row_response(&response);
+                response
+            }
+            None => self.dummy_response(),
+        }
+    }
+    /// Adds header cell (see [`Self::header_row`]) that changes sorting of rows when clicked,
+    /// clicking column by which rows are already sorted reverses order.
+    /// Use [`Self::sort_order`] to get the order in which rows should be added.
+    pub fn sort_header(&mut self, text: impl Into<String>) -> Response {
+        let text: String = text.into();
+        let column = self.state.column;
+        if self.state.header {
+            let names = &mut self.state.column_names;
+            names.resize(names.len().max(column + 1), String::new());
+            names[column] = text.clone();
+        }
+        let text = match self.state.sort {
+            Some(sort) if sort.column == column && sort.ascending => format!("{text} ⏶"),
+            Some(sort) if sort.column == column => format!("{text} ⏷"),
+            _ => text,
+        };
+        let label = Label::new(RichText::new(text).strong()).sense(Sense::click());
+        let response = self.add_ex_opt(|ui| ui.add(label));
+        let response = response.unwrap_or(self.dummy_response());
+        if response.clicked() {
+            let ascending = self.state.sort
+                != Some(ColumnSort {
+                    column,
+                    ascending: true,
+                });
+            self.state.sort = Some(ColumnSort { column, ascending });
+        }
+        response.on_hover_cursor(CursorIcon::PointingHand)
+    }
+
+    /// Widths of columns set by resizing them (see [`ExGrid::resizable_columns`]) or with [`Self::set_column_width`],
+    /// `None` means that column fits its content
+    pub fn column_widths(&self) -> &[Option<f32>] {
+        &self.state.column_widths
+    }
+
+    /// Set width of the `column` (Traditional mode), `None` makes column fit its content.
+    /// Width is kept in memory, so it is enough to call it once.
+    pub fn set_column_width(&mut self, column: usize, width: Option<f32>) {
+        let widths = &mut self.state.column_widths;
+        if widths.len() <= column {
+            widths.resize(column + 1, None);
         }
-        .unwrap_or(self.dummy_response())
+        widths[column] = width;
+        while widths.last() == Some(&None) {
+            widths.pop();
+        }
+    }
+
+    /// Current sorting of rows (changed by clicking [`Self::sort_header`])
+    pub fn sort_state(&self) -> Option<ColumnSort> {
+        self.state.sort
+    }
+
+    /// Returns indices of `total_rows` top level rows in the order in which they should be added,
+    /// `compare(column, a, b)` compares rows `a` and `b` by value in `column`.
+    ///
+    /// Each row should be added together with its subrows (eg. [`Self::collapsing_rows`]),
+    /// state of subrows (eg. whether they are collapsed) follows their parent when it changes position.
+    pub fn sort_order(
+        &mut self,
+        total_rows: usize,
+        compare: impl Fn(usize, usize, usize) -> std::cmp::Ordering,
+    ) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..total_rows).collect();
+        if let Some(ColumnSort { column, ascending }) = self.state.sort {
+            if ascending {
+                order.sort_by(|&a, &b| compare(column, a, b));
+            } else {
+                order.sort_by(|&a, &b| compare(column, b, a));
+            }
+        }
+        self.state.sort_order = Some(order.clone());
+        order
     }
+
     pub fn dummy_response(&mut self) -> Response {
         self.interact(
             egui::Rect::NOTHING,
@@ -500,6 +2037,7 @@ impl<'a, 'b> ExUi<'a, 'b> {
         {
             *nesting_count += 1;
         } else {
+            self.state.validation.keep_cell = Some(self.state.row_cursor.clone());
             let ui: &mut Ui = &mut *self;
             self.keep_cell = Some(ExUiKeepCell {
                 ui: MaybeOwnedMut::Owned(simpleui(ui)),
@@ -509,6 +2047,12 @@ impl<'a, 'b> ExUi<'a, 'b> {
         }
     }
     pub fn keep_cell_stop(&mut self) {
+        if self.keep_cell.is_none() {
+            self.validation_error("keep_cell_stop without matching keep_cell_start");
+        }
+        self.keep_cell_end();
+    }
+    fn keep_cell_end(&mut self) {
         if let Some(ExUiKeepCell {
             ref mut nesting_count,
             ref ui,
@@ -517,9 +2061,11 @@ impl<'a, 'b> ExUi<'a, 'b> {
         {
             if *nesting_count == 0 {
                 let rect = ui.min_rect();
-                self._ui().advance_cursor_after_rect(rect);
+                let clip_rect = ui.clip_rect();
+                self.finish_cell(rect, clip_rect);
                 self.temp_ui = None;
                 self.keep_cell = None;
+                self.state.validation.keep_cell = None;
             } else {
                 *nesting_count -= 1;
             }
@@ -531,11 +2077,40 @@ impl<'a, 'b> ExUi<'a, 'b> {
     /// Until `Self::stop_disabled` all widgets will be added in disabled state
     /// Nested calls are allowed and require matching number of `Self::stop_disabled` to reenable Ui
     pub fn start_disabled(&mut self) {
-        self.state.disabled += 1
+        self.state.disabled += 1;
+        let cursor = self.state.row_cursor.clone();
+        self.state.validation.disabled.push(cursor);
     }
     /// If Ui has been disabled with `Self::start_disabled`, reenable it
     pub fn stop_disabled(&mut self) {
-        self.state.disabled.overflowing_sub(1).0;
+        match self.state.disabled.checked_sub(1) {
+            Some(disabled) => {
+                self.state.disabled = disabled;
+                self.state.validation.disabled.pop();
+            }
+            None => self.validation_error("stop_disabled without matching start_disabled"),
+        }
+    }
+    /// Rows added by `add_rows` are disabled if `enabled` is false
+    /// (same as surrounding them with `Self::start_disabled`/`Self::stop_disabled`)
+    pub fn add_enabled_rows<R>(
+        &mut self,
+        enabled: bool,
+        add_rows: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        if !enabled {
+            self.start_disabled();
+        }
+        let ret = add_rows(self);
+        if !enabled {
+            self.stop_disabled();
+        }
+        ret
+    }
+    /// Text shown when current row is hovered, if any of its cells is disabled (eg. explaining why it is disabled).
+    /// It is attached to the row response (see [`RowResponse`]), so it is shown in both modes.
+    pub fn disabled_reason(&mut self, reason: impl Into<WidgetText>) {
+        self.state.disabled_reason = Some(reason.into());
     }
 }
 #[allow(nonstandard_style)]
@@ -560,6 +2135,148 @@ impl<'a, 'b> ExUi<'a, 'b> {
     }
 }
 
+/// Id of the row identified by `key` (see [`ExUi::row_key`])
+fn row_key_id(grid_id: Id, key: impl std::hash::Hash) -> Id {
+    grid_id.with(("row_key", key))
+}
+
+/// Id of the collapsing header row identified by `key` (see [`ExUi::collapsing_rows_keyed`])
+pub(crate) fn keyed_header_id(grid_id: Id, key: impl std::hash::Hash) -> Id {
+    // the same as `ExUi::cursor_id` of the first row nested in the row with key
+    row_key_id(grid_id, key).with(&[0usize][..])
+}
+
+/// Button (or custom icon, see [`ExGrid::collapse_icon`]) toggling collapsing rows, returns `true` if it was clicked
+fn collapse_toggle(
+    ui: &mut Ui,
+    collapsed: bool,
+    openness: f32,
+    icon_fn: Option<CollapseIconFn>,
+) -> bool {
+    match icon_fn {
+        Some(icon_fn) => {
+            let size = Vec2::splat(ui.spacing().icon_width);
+            let (_, response) = ui.allocate_exact_size(size, Sense::click());
+            icon_fn(ui, openness, &response);
+            response.clicked()
+        }
+        None => {
+            let icon = if collapsed { "⏵" } else { "⏷" };
+            ui.add(Button::new(icon).frame(false).small()).clicked()
+        }
+    }
+}
+
+/// Buttons expanding and collapsing all rows of the grid (see [`ExGrid::expand_buttons`])
+fn expand_buttons(ui: &mut Ui, grid_id: Id) {
+    let expand = ui.add(Button::new("⏷").frame(false).small());
+    if expand.on_hover_text("Expand all").clicked() {
+        ExGrid::expand_all(ui.ctx(), grid_id);
+    }
+    let collapse = ui.add(Button::new("⏵").frame(false).small());
+    if collapse.on_hover_text("Collapse all").clicked() {
+        ExGrid::collapse_all(ui.ctx(), grid_id);
+    }
+}
+
+/// Store collapse state of rows under `id` (see [`ExGrid::persist_collapsed`])
+fn store_collapsed(ctx: &Context, id: Id, collapsed: bool, persist: bool) {
+    ctx.data_mut(|d| match persist {
+        true => d.insert_persisted(id, collapsed),
+        false => d.insert_temp(id, collapsed),
+    });
+}
+
+/// Sub-ui for single cell of `egui::Grid`,
+/// similarly to `egui::Grid` content is vertically centered within the row
+fn grid_cell_ui(ui: &mut Ui, max_rect: Rect, align: Align, id_salt: Id) -> Ui {
+    let mut cell = cell_child_ui(ui, max_rect, cell_layout(align), id_salt);
+    inherit_grid_wrap(ui, &mut cell);
+    cell
+}
+
+/// Rect available for the current cell of `egui::Grid`
+fn grid_cell_rect(ui: &Ui, row_height: f32) -> Rect {
+    let mut max_rect = ui.available_rect_before_wrap();
+    max_rect.max.y = max_rect.min.y + row_height;
+    max_rect
+}
+
+/// Layout of the cell content aligned horizontally with `align` (see [`ColumnSpec::align`])
+fn cell_layout(align: Align) -> Layout {
+    match align {
+        Align::Min => Layout::left_to_right(Align::Center),
+        // horizontal layouts can not center their content
+        Align::Center => Layout::top_down(Align::Center),
+        Align::Max => Layout::right_to_left(Align::Center),
+    }
+}
+
+/// Sub-ui of the cell, `id_salt` makes ids of its widgets independent of preceding cells
+fn cell_child_ui(ui: &mut Ui, max_rect: Rect, layout: Layout, id_salt: Id) -> Ui {
+    #[cfg(feature = "egui29")]
+    {
+        ui.new_child(UiBuilder {
+            id_salt: Some(id_salt),
+            max_rect: Some(max_rect),
+            layout: Some(layout),
+            ..Default::default()
+        })
+    }
+    #[cfg(not(feature = "egui29"))]
+    {
+        ui.child_ui_with_id_source(
+            max_rect,
+            layout,
+            id_salt,
+            #[cfg(feature = "egui28")]
+            None,
+        )
+    }
+}
+
+/// Cell sub-ui is not a grid, so it has to inherit wrapping behavior of the grid
+fn inherit_grid_wrap(grid: &Ui, cell: &mut Ui) {
+    #[cfg(any(feature = "egui28", feature = "egui29"))]
+    {
+        cell.style_mut().wrap_mode = Some(grid.wrap_mode());
+    }
+    #[cfg(not(any(feature = "egui28", feature = "egui29")))]
+    {
+        cell.style_mut().wrap = Some(grid.wrap_text());
+    }
+}
+
+/// Make text in the cell wrap (see [`ColumnSpec::wrap`])
+fn wrap_text(cell: &mut Ui) {
+    #[cfg(any(feature = "egui28", feature = "egui29"))]
+    {
+        cell.style_mut().wrap_mode = Some(TextWrapMode::Wrap);
+    }
+    #[cfg(not(any(feature = "egui28", feature = "egui29")))]
+    {
+        cell.style_mut().wrap = Some(true);
+    }
+}
+
+/// Apply [`ColumnSpec`] to the cell in `CompactWidth` layout (limits apply to content added after the column label),
+/// values follow each other, so they are not aligned
+fn compact_column_spec(cell: &mut Ui, spec: &ColumnSpec) {
+    let label_width = cell.cursor().left() - cell.max_rect().left();
+    if let Some(max) = spec.max {
+        cell.set_max_width(label_width + max);
+        let mut clip_rect = cell.clip_rect();
+        clip_rect.max.x = clip_rect.max.x.min(cell.max_rect().right());
+        cell.set_clip_rect(clip_rect);
+    }
+    if let Some(min) = spec.min {
+        cell.set_min_width(label_width + min);
+    }
+    if spec.wrap {
+        wrap_text(cell);
+    }
+}
+
 fn simpleui(ui: &mut Ui) -> Ui {
     let max_rect = ui.available_rect_before_wrap();
     let layout = Layout::left_to_right(Default::default());
@@ -591,9 +2308,22 @@ impl<'a, 'b, 'c> CollapsingRows<'a, 'b, 'c> {
     /// Set initial collapse state of this level collapsible rows. Function will be executed once for each collapsible
     pub fn initial_state(self, start_collapsed: impl FnOnce() -> bool) -> Self {
         let id = self.exui.id();
-        self.exui
-            .ui
-            .data_mut(|d| d.get_temp_mut_or_insert_with(id, start_collapsed).clone());
+        let mut inserted = None;
+        let start_collapsed = || *inserted.insert(start_collapsed());
+        if self.exui.state.persist_collapsed {
+            self.exui
+                .ui
+                .data_mut(|d| *d.get_persisted_mut_or_insert_with(id, start_collapsed));
+        } else {
+            self.exui
+                .ui
+                .data_mut(|d| d.get_temp_mut_or_insert_with(id, start_collapsed).clone());
+        }
+        if let Some(collapsed) = inserted {
+            // initial state is not animated
+            let ctx = self.exui.ctx();
+            ctx.animate_bool_with_time(id.with("openness"), !collapsed, 0.0);
+        }
 
         self
     }
@@ -602,21 +2332,89 @@ impl<'a, 'b, 'c> CollapsingRows<'a, 'b, 'c> {
         self,
         collapsing_rows: impl FnOnce(&mut ExUi) -> Response,
     ) -> CollapsingResponse<()> {
+        let header_response = self.header_response.clone();
+        let (_, body_response, openness, toggled) = self.add_body(collapsing_rows);
+        CollapsingResponse {
+            header_response,
+            header_returned: (),
+            body_response,
+            body_returned: None,
+            openness,
+            toggled,
+        }
+    }
+    /// Add rows with subdata that may be not available yet (eg. they are fetched by slow query when rows are expanded).
+    /// `collapsing_rows` is called only when `status` is [`BodyStatus::Ready`], otherwise row with spinner
+    /// (or with error message) is shown when rows are expanded. Use [`LazyCollapsingResponse::just_expanded`] to start loading.
+    pub fn body_lazy(
+        self,
+        status: BodyStatus,
+        collapsing_rows: impl FnOnce(&mut ExUi) -> Response,
+    ) -> LazyCollapsingResponse {
+        let id = self.exui.id();
+        let ctx = self.exui.ctx().clone();
+        let header_response = self.header_response.clone();
+        let (_, body_response, openness, _) = self.add_body(|ui| match status {
+            BodyStatus::Ready => Some(collapsing_rows(ui)),
+            BodyStatus::Loading => {
+                ui.add(Spinner::new());
+                ui.end_row();
+                None
+            }
+            BodyStatus::Error(error) => {
+                let color = ui.visuals().error_fg_color;
+                ui.add(Label::new(RichText::new(error).color(color)));
+                ui.end_row();
+                None
+            }
+        });
+        let shown = body_response.is_some();
+        let shown_prev = ctx.data_mut(|d| {
+            let shown_prev = d.get_temp(id.with("lazy_shown"));
+            d.insert_temp(id.with("lazy_shown"), shown);
+            shown_prev
+        });
+        LazyCollapsingResponse {
+            header_response,
+            body_response: body_response.flatten(),
+            openness,
+            just_expanded: shown && shown_prev != Some(true),
+        }
+    }
+    /// Ends header row and adds subrows (if they are not collapsed),
+    /// returns response of the header row, value returned by `collapsing_rows`, openness
+    /// and new collapse state if it was changed since previous frame (see [`CollapsingResponse::just_opened`])
+    fn add_body<R>(
+        self,
+        collapsing_rows: impl FnOnce(&mut ExUi) -> R,
+    ) -> (RowResponse, Option<R>, f32, Option<bool>) {
         let id = self.exui.id();
-        let collapsed = self
-            .exui
+        let hidden = self.exui.collapsed();
+        // when rows are filtered, collapsed rows are also searched for matches,
+        // requested collapse state has to reach also rows nested in collapsed ones
+        let filtered =
+            self.exui.state.filter.is_some() || self.exui.state.collapse_request.is_some();
+        let mut body_returned = None;
+        let header_response = self.exui.end_row();
+        // header row can toggle rows when it ends (see `ExGrid::clickable_headers`)
+        let openness = self.exui.header_openness(id);
+        let collapsed = openness == 0.0;
+        let open = !self.exui.header_collapsed(id);
+        let open_prev: Option<bool> = self.exui.ui.data_mut(|d| d.get_temp(id.with("open_prev")));
+        self.exui
             .ui
-            .data_mut(|d| d.get_temp_mut_or(id, false).clone());
-        let mut ret = CollapsingResponse {
-            header_response: self.header_response.clone(),
-            body_response: None,
-            body_returned: None,
-            openness: !collapsed as usize as f32,
-        };
-        self.exui.end_row();
+            .data_mut(|d| d.insert_temp(id.with("open_prev"), open));
+        let toggled = open_prev
+            .filter(|open_prev| *open_prev != open)
+            .map(|_| open);
         if self.collapsible {
-            if collapsed {
-                if let ExUiMode::Compact { ref mut ui_row, .. } = self.exui.state.mode {
+            if collapsed && !hidden {
+                let ExUiInner {
+                    mode,
+                    collapsed_placeholder,
+                    ..
+                } = self.exui.state.as_mut();
+                if let ExUiMode::Compact { ref mut ui_row, .. } = mode {
                     let ui = &mut ui_row.last_mut().unwrap().content_ui;
                     #[cfg(feature = "egui29")]
                     let mut child = ui.new_child(UiBuilder {
@@ -631,23 +2429,261 @@ impl<'a, 'b, 'c> CollapsingRows<'a, 'b, 'c> {
                         #[cfg(feature = "egui28")]
                         None,
                     );
-                    child.label("⚫⚫⚫");
+                    match collapsed_placeholder {
+                        Some(add_placeholder) => add_placeholder(&mut child),
+                        None => _ = child.label("⚫⚫⚫"),
+                    }
                     ui.expand_to_include_rect(child.min_rect())
                 }
-            } else {
-                ret.body_response = Some(collapsing_rows(self.exui));
+            }
+            // subrows can be also hidden by animation of outer rows
+            if !self.exui.level_collapsed() {
+                body_returned = Some(collapsing_rows(self.exui));
+            } else if filtered {
+                collapsing_rows(self.exui);
             }
             self.exui.stop_collapsing();
         }
-        ret
+        (header_response, body_returned, openness, toggled)
     }
     /// Same as [`Self::body`] but returns summed responses from body & header
     pub fn body_simple(self, collapsing_rows: impl FnOnce(&mut ExUi) -> Response) -> Response {
         let cr = self.body(collapsing_rows);
-        if let Some(body) = cr.body_response {
+        // responses of rows hidden (eg. by animation) are not placed in the layer of the grid
+        let body = cr.body_response;
+        if let Some(body) = body.filter(|body| body.layer_id == cr.header_response.layer_id) {
             body | cr.header_response
         } else {
             cr.header_response
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_matches_ascii() {
+        assert_eq!(find_matches("Hello hello", "hello"), vec![0..5, 6..11]);
+        assert_eq!(find_matches("aaaa", "aa"), vec![0..2, 2..4]);
+        assert_eq!(find_matches("abc", "abcd"), vec![]);
+        assert_eq!(find_matches("abc", ""), vec![]);
+    }
+
+    #[test]
+    fn find_matches_returns_byte_ranges() {
+        // 'Ä' and 'ß' take 2 bytes each
+        assert_eq!(find_matches("xÄby", &to_lowercase("äB")), vec![1..4]);
+        assert_eq!(find_matches("Straße", "ße"), vec![4..7]);
+        assert_eq!(find_matches("ÄÄ", "ä"), vec![0..2, 2..4]);
+    }
+
+    #[test]
+    fn find_matches_lowercase_keeps_char_count() {
+        // 'İ' lowercases into 2 chars, only the first one is used
+        let query = to_lowercase("İST");
+        assert_eq!(query.chars().count(), 3);
+        assert_eq!(find_matches("İstanbul", &query), vec![0..4]);
+        assert_eq!(find_matches("İİx", &to_lowercase("İx")), vec![2..5]);
+    }
+
+    #[test]
+    fn highlight_matches_sections() {
+        let style = Style::default();
+        let sections = |text: &str, matches: &[std::ops::Range<usize>]| {
+            let job = highlight_matches(RichText::new(text), matches, &style);
+            assert_eq!(job.text, text);
+            job.sections
+                .into_iter()
+                .map(|s| {
+                    (
+                        s.byte_range,
+                        s.format.background == style.visuals.selection.bg_fill,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sections("xÄby", &[1..4]),
+            vec![(0..1, false), (1..4, true), (4..5, false)]
+        );
+        assert_eq!(
+            sections("abab", &[0..2, 2..4]),
+            vec![(0..2, true), (2..4, true)]
+        );
+        assert_eq!(sections("abc", &[]), vec![(0..3, false)]);
+    }
+
+    fn selection(multi: bool, selected: &[usize]) -> Selection {
+        Selection {
+            multi,
+            selected: selected.iter().map(|&row| vec![row]).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Shows rows `0..5` and clicks `row`
+    fn click(selection: &mut Selection, row: usize, modifiers: Modifiers) {
+        selection.rows = (0..5).map(|row| vec![row]).collect();
+        selection.clicked = Some((vec![row], modifiers));
+        selection.finish();
+        assert!(selection.rows.is_empty());
+    }
+
+    /// Shows rows `0..5` and presses ArrowUp (`up`) or ArrowDown on `row`
+    fn key_move(selection: &mut Selection, row: usize, up: bool, modifiers: Modifiers) {
+        selection.rows = (0..5).map(|row| vec![row]).collect();
+        selection.key_move = Some((vec![row], up, modifiers));
+        selection.finish();
+    }
+
+    #[test]
+    fn selection_click_replaces_selection() {
+        let mut selection = selection(true, &[1, 2]);
+        click(&mut selection, 3, Modifiers::NONE);
+        assert_eq!(selection.selected, vec![vec![3]]);
+        assert_eq!(selection.anchor, Some(vec![3]));
+    }
+
+    #[test]
+    fn selection_shift_selects_range_from_anchor() {
+        let mut selection = selection(true, &[]);
+        click(&mut selection, 3, Modifiers::NONE);
+        click(&mut selection, 1, Modifiers::SHIFT);
+        assert_eq!(selection.selected, vec![vec![1], vec![2], vec![3]]);
+        // range is replaced, anchor is kept
+        click(&mut selection, 4, Modifiers::SHIFT);
+        assert_eq!(selection.selected, vec![vec![3], vec![4]]);
+        assert_eq!(selection.anchor, Some(vec![3]));
+    }
+
+    #[test]
+    fn selection_command_toggles_and_extends() {
+        let mut selection = selection(true, &[]);
+        click(&mut selection, 0, Modifiers::NONE);
+        click(&mut selection, 2, Modifiers::COMMAND);
+        assert_eq!(selection.selected, vec![vec![0], vec![2]]);
+        click(&mut selection, 0, Modifiers::COMMAND);
+        assert_eq!(selection.selected, vec![vec![2]]);
+        // Shift+Command adds range to the selection (without duplicates)
+        click(&mut selection, 4, Modifiers::SHIFT | Modifiers::COMMAND);
+        assert_eq!(
+            selection.selected,
+            vec![vec![2], vec![0], vec![1], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn selection_shift_without_anchor_or_multi() {
+        let mut single = selection(false, &[]);
+        click(&mut single, 1, Modifiers::NONE);
+        click(&mut single, 3, Modifiers::SHIFT);
+        assert_eq!(single.selected, vec![vec![3]]);
+        click(&mut single, 4, Modifiers::COMMAND);
+        assert_eq!(single.selected, vec![vec![4]]);
+
+        // anchor row is not shown anymore
+        let mut multi = selection(true, &[]);
+        multi.anchor = Some(vec![7]);
+        click(&mut multi, 2, Modifiers::SHIFT);
+        assert_eq!(multi.selected, vec![vec![2]]);
+    }
+
+    /// Shows `add_rows` in a grid with multiple selection, clicks text `label` and returns selected rows
+    fn click_row(mode: GridMode, label: &str, add_rows: impl Fn(&mut ExUi)) -> Vec<Vec<usize>> {
+        let ctx = Context::default();
+        let mut pos = None;
+        let mut selected = Vec::new();
+        for frame in 0..8 {
+            let mut input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(600.0, 400.0))),
+                ..Default::default()
+            };
+            if let Some(pos) = pos {
+                let button = |pressed| Event::PointerButton {
+                    pos,
+                    button: PointerButton::Primary,
+                    pressed,
+                    modifiers: Modifiers::NONE,
+                };
+                match frame {
+                    4 => input.events.push(Event::PointerMoved(pos)),
+                    5 => input.events.push(button(true)),
+                    6 => input.events.push(button(false)),
+                    _ => {}
+                }
+            }
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let grid = ExGrid::new("grid").mode(mode.clone());
+                    let response = grid
+                        .selection(SelectionMode::Multi)
+                        .show(ui, |ui| add_rows(ui));
+                    selected = response.selected;
+                });
+            });
+            pos = pos.or_else(|| {
+                output.shapes.iter().find_map(|shape| match &shape.shape {
+                    Shape::Text(text) if text.galley.text() == label => {
+                        Some(text.visual_bounding_rect().center())
+                    }
+                    _ => None,
+                })
+            });
+        }
+        selected
+    }
+
+    #[test]
+    fn selection_click_on_body_row_left_open() {
+        for mode in [GridMode::Traditional, GridMode::CompactWidth] {
+            let selected = click_row(mode, "n2", |ui| {
+                ui.extext("a");
+                ui.end_row();
+                ui.collapsing_rows(|ui| ui.extext("h")).body_simple(|ui| {
+                    ui.extext("n1");
+                    ui.end_row();
+                    ui.extext("n2")
+                });
+                ui.extext("b");
+                ui.end_row();
+            });
+            assert_eq!(selected, vec![vec![1, 2]]);
+        }
+    }
+
+    #[test]
+    fn last_row_left_open_is_interactive() {
+        for mode in [GridMode::Traditional, GridMode::CompactWidth] {
+            let selected = click_row(mode, "c", |ui| {
+                for label in ["a", "b"] {
+                    ui.extext(label);
+                    ui.end_row();
+                }
+                ui.extext("c");
+            });
+            assert_eq!(selected, vec![vec![2]]);
+        }
+    }
+
+    #[test]
+    fn selection_arrows_move_selection_and_focus() {
+        let mut selection = selection(true, &[]);
+        click(&mut selection, 1, Modifiers::NONE);
+        key_move(&mut selection, 1, false, Modifiers::NONE);
+        assert_eq!(selection.selected, vec![vec![2]]);
+        assert_eq!(selection.focus, Some(vec![2]));
+        key_move(&mut selection, 2, false, Modifiers::SHIFT);
+        assert_eq!(selection.selected, vec![vec![2], vec![3]]);
+        // Command moves only focus
+        key_move(&mut selection, 3, true, Modifiers::COMMAND);
+        assert_eq!(selection.selected, vec![vec![2], vec![3]]);
+        assert_eq!(selection.focus, Some(vec![2]));
+        // nothing above the first row
+        selection.focus = None;
+        key_move(&mut selection, 0, true, Modifiers::NONE);
+        assert_eq!(selection.selected, vec![vec![2], vec![3]]);
+        assert_eq!(selection.focus, None);
+    }
+}
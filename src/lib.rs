@@ -16,6 +16,7 @@ use egui29 as egui;
 
 use egui::layers::ShapeIdx;
 use egui::*;
+use std::sync::Arc;
 
 mod exui;
 mod ui_wrapper;
@@ -25,18 +26,194 @@ pub use exui::*;
 /// Configures [`ExGrid`] Layout
 /// - `Traditional` - will result in traditional grid layout (row is in single line)
 /// - `CompactWidth` - will result in not grid layout (width-compact, `egui::Group` based layout)
-// /// - `Auto0Wrap` - decision between above will be taken based on available and content width(assuming that in grid mode content will not wrap)
-// /// - `AutoOptimalWrap` - similar to `Auto0Wrap` but in grid mode cell content(if necessary) will wrap to some extent
+/// - `Auto0Wrap` - decision between above will be taken based on available and content width(assuming that in grid mode content will not wrap)
+/// - `AutoOptimalWrap` - similar to `Auto0Wrap` but in grid mode cell content(if necessary) will wrap to some extent
+///   (widest columns are wrapped, but not below [`ExGrid::min_wrap_width`])
 pub enum GridMode {
-    // Auto0Wrap,
-    // AutoOptimalWrap,
+    Auto0Wrap,
+    AutoOptimalWrap,
     #[default]
     CompactWidth,
     Traditional,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// Configures how values are labeled with their column names in `CompactWidth` layout
+/// (column names are set with [`ExGrid::column_names`] or [`ExUi::header_row`])
+/// - `None` - values are not labeled
+/// - `Inline` - values are preceded by label (eg. "Name: foo") and wrapped as usual
+/// - `KeyValue` - each value is placed in separate line, forming two-column (label | value) grid inside the row
+pub enum CompactLabels {
+    None,
+    #[default]
+    Inline,
+    KeyValue,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Column by which rows of [`ExGrid`] are sorted (see [`ExUi::sort_header`] and [`ExUi::sort_order`])
+pub struct ColumnSort {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Layout of cells of single column (see [`ExGrid::column`])
+/// - `min` - minimal width of the column
+/// - `max` - maximal width of the column, content that does not fit is wrapped (if `wrap` is set) or clipped
+/// - `align` - horizontal alignment of content within the column (Traditional mode only,
+///   in `CompactWidth` layout values follow each other, so they are not aligned),
+///   centered content is placed at the top of the row
+/// - `wrap` - text is wrapped (at `max` width or at the edge of the grid/row) instead of extending the column
+pub struct ColumnSpec {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub align: Align,
+    pub wrap: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// Configures selection of [`ExGrid`] rows (row is selected by clicking anywhere in it, ArrowUp/ArrowDown move selection from the focused row)
+/// - `None` - rows can not be selected
+/// - `Single` - single row can be selected
+/// - `Multi` - multiple rows can be selected, Ctrl toggles selection of clicked row, Shift selects range of rows (also with arrows)
+pub enum SelectionMode {
+    #[default]
+    None,
+    Single,
+    Multi,
+}
+
+/// Response of [`ExGrid::show`] (it is also convertible to [`InnerResponse`])
+pub struct ExGridResponse<R> {
+    pub inner: R,
+    pub response: Response,
+    /// Id of the grid (`ui.make_persistent_id(id_source)` of the parent `ui`), used by eg. [`ExGrid::set_collapsed`]
+    pub id: Id,
+    /// Paths of selected rows (see [`ExGrid::selection`] and [`ExUi::get_nesting_cursor`]),
+    /// top level row is identified by its index in data if rows are sorted (see [`ExUi::sort_order`])
+    pub selected: Vec<Vec<usize>>,
+}
+
+impl<R> From<ExGridResponse<R>> for InnerResponse<R> {
+    fn from(response: ExGridResponse<R>) -> Self {
+        InnerResponse::new(response.inner, response.response)
+    }
+}
+
+/// Response of the whole row (returned by [`ExUi::end_row`]), dereferences to [`Response`]
+pub struct RowResponse {
+    /// Interaction over the row rect united with responses of cells added with [`ExUi`] methods (eg. [`ExUi::add`], [`ExUi::extext`]).
+    /// Widgets of the row stay interactive, so clicks on widgets that sense them (eg. buttons, selectable labels) are not registered by the row.
+    ///
+    /// Row is interactive where it was in previous frame. It is dummy response if row is hidden, empty or it is header row.
+    pub response: Response,
+    /// Row is not shown (it is collapsed or filtered out)
+    pub hidden: bool,
+}
+
+impl std::ops::Deref for RowResponse {
+    type Target = Response;
+    fn deref(&self) -> &Self::Target {
+        &self.response
+    }
+}
+
+/// Value returned by closure adding the row together with response of the row (see [`ExUi::row`])
+pub struct RowInnerResponse<R> {
+    pub inner: R,
+    pub response: RowResponse,
+}
+
+/// Response of collapsing rows (see [`CollapsingRows::body`], [`ExUi::collapsing`]),
+/// same as `egui::CollapsingResponse`, but it also tells if rows were just expanded or collapsed
+pub struct CollapsingResponse<R, H = ()> {
+    /// Response of the header row
+    pub header_response: Response,
+    /// Value returned by closure adding the header row (see [`ExUi::collapsing`])
+    pub header_returned: H,
+    /// None iff collapsed.
+    pub body_response: Option<Response>,
+    /// None iff collapsed.
+    pub body_returned: Option<R>,
+    /// 0.0 if fully closed, 1.0 if fully open, and something in-between while animating.
+    pub openness: f32,
+    /// New collapse state (`true` - expanded) if it was changed in this frame
+    pub(crate) toggled: Option<bool>,
+}
+
+impl<R, H> CollapsingResponse<R, H> {
+    /// Were rows fully closed (and not being animated)?
+    pub fn fully_closed(&self) -> bool {
+        self.openness <= 0.0
+    }
+
+    /// Were rows fully open (and not being animated)?
+    pub fn fully_open(&self) -> bool {
+        self.openness >= 1.0
+    }
+
+    /// Rows were expanded in this frame (by the user or with [`ExGrid::expand_all`] etc.).
+    /// It is also reported when rows are forced open by search filter (see [`ExGrid::filter`]) or when filter is cleared
+    pub fn just_opened(&self) -> bool {
+        self.toggled == Some(true)
+    }
+
+    /// Rows were collapsed in this frame (by the user or with [`ExGrid::collapse_all`] etc.).
+    /// It is also reported when rows are forced closed by search filter (see [`ExGrid::filter`]) or when filter is cleared
+    pub fn just_closed(&self) -> bool {
+        self.toggled == Some(false)
+    }
+}
+
+/// Availability of subrows loaded lazily (see [`CollapsingRows::body_lazy`])
+#[derive(Clone, Debug, PartialEq)]
+pub enum BodyStatus {
+    /// Subrows are available, they are added as usual
+    Ready,
+    /// Subrows are being loaded, spinner row is shown in place of them
+    Loading,
+    /// Loading of subrows failed, row with the error message is shown in place of them
+    Error(String),
+}
+
+/// Response of collapsing rows with subrows loaded lazily (see [`CollapsingRows::body_lazy`])
+pub struct LazyCollapsingResponse {
+    /// Response of the header row
+    pub header_response: Response,
+    /// Response of subrows (`None` if they are collapsed or not [`BodyStatus::Ready`])
+    pub body_response: Option<Response>,
+    /// 0.0 - collapsed, 1.0 - expanded
+    pub openness: f32,
+    pub(crate) just_expanded: bool,
+}
+
+impl LazyCollapsingResponse {
+    /// Subrows were expanded in this frame (or they are shown expanded for the first time),
+    /// so it is time to start loading them
+    pub fn just_expanded(&self) -> bool {
+        self.just_expanded
+    }
+}
+
 // ----------------------------------------------------------------------------
 
+// type alias for boxed function to determine frame of the row (depending on nesting level) in `CompactWidth` mode
+pub(crate) type FramePickerFn = Box<dyn Send + Sync + Fn(usize, &Style) -> Frame>;
+// type alias for shared function to determine row color (shared, as it is used by both `egui::Grid` and `ExUi`)
+pub(crate) type ColorPickerFn = Arc<dyn Send + Sync + Fn(usize, &Style) -> Option<Color32>>;
+// type alias for function painting icon of collapsing header (like `CollapsingHeader::icon`)
+pub(crate) type CollapseIconFn = fn(&mut Ui, f32, &Response);
+// type alias for boxed function adding content shown in place of collapsed rows in `CompactWidth` mode
+pub(crate) type PlaceholderFn = Box<dyn Send + Sync + Fn(&mut Ui)>;
+
+fn striped_row_color(row: usize, style: &Style) -> Option<Color32> {
+    if row % 2 == 1 {
+        return Some(style.visuals.faint_bg_color);
+    }
+    None
+}
+
 /// ExGrid- drop-in replacement for [`egui::Grid`] with superpowers:
 ///
 /// - alternative/improved layout mode dedicated to narrow windows (it's not grid there, but rather some group based layout)
@@ -65,27 +242,77 @@ pub enum GridMode {
 /// ```
 #[must_use = "You should call .show()"]
 pub struct ExGrid {
+    id_source: Id,
     grid: Grid,
     mode: GridMode,
+    auto_hysteresis: f32,
+    min_wrap_width: f32,
+    min_col_width: Option<f32>,
+    max_col_width: Option<f32>,
+    min_row_height: Option<f32>,
+    spacing: Option<Vec2>,
+    compact_frame: Option<FramePickerFn>,
+    color_picker: Option<ColorPickerFn>,
+    start_row: usize,
+    column_names: Vec<String>,
+    compact_labels: CompactLabels,
+    search_box: bool,
+    filter: Option<String>,
+    selection: SelectionMode,
+    resizable_columns: bool,
+    columns: Vec<ColumnSpec>,
+    persist_collapsed: bool,
+    expand_buttons: bool,
+    animate: bool,
+    collapse_icon: Option<CollapseIconFn>,
+    collapsed_placeholder: Option<PlaceholderFn>,
+    clickable_headers: bool,
+    indent_width: Option<f32>,
 }
 
 impl ExGrid {
     /// Create a new [`ExGrid`] with a locally unique identifier.
     pub fn new(id_source: impl std::hash::Hash) -> Self {
+        let id_source = Id::new(id_source);
         Self {
+            id_source,
             grid: Grid::new(id_source),
             mode: Default::default(),
+            auto_hysteresis: 16.0,
+            min_wrap_width: 100.0,
+            min_col_width: None,
+            max_col_width: None,
+            min_row_height: None,
+            spacing: None,
+            compact_frame: None,
+            color_picker: None,
+            start_row: 0,
+            column_names: Vec::new(),
+            compact_labels: Default::default(),
+            search_box: false,
+            filter: None,
+            selection: Default::default(),
+            resizable_columns: false,
+            columns: Vec::new(),
+            persist_collapsed: false,
+            expand_buttons: false,
+            animate: true,
+            collapse_icon: None,
+            collapsed_placeholder: None,
+            clickable_headers: false,
+            indent_width: None,
         }
     }
 
     /// Setting this will allow for dynamic coloring of rows of the grid object
-    /// Matters only in Grid view
+    /// In `CompactWidth` layout color is used as background of the row frame
+    /// (rows are numbered the same way as in Grid view, nested rows included)
     #[inline]
     pub fn with_row_color<F>(mut self, color_picker: F) -> Self
     where
         F: Send + Sync + Fn(usize, &Style) -> Option<Color32> + 'static,
     {
-        self.grid = self.grid.with_row_color(color_picker);
+        self.color_picker = Some(Arc::new(color_picker));
         self
     }
 
@@ -101,10 +328,13 @@ impl ExGrid {
     ///
     /// This can make a table easier to read.
     /// Default is whatever is in [`crate::Visuals::striped`].
-    /// Matters only in Grid view
-    pub fn striped(mut self, striped: bool) -> Self {
-        self.grid = self.grid.striped(striped);
-        self
+    pub fn striped(self, striped: bool) -> Self {
+        if striped {
+            self.with_row_color(striped_row_color)
+        } else {
+            // Explicitly set the row color to nothing.
+            self.with_row_color(|_row: usize, _style: &Style| None)
+        }
     }
 
     /// Set minimum width of each column.
@@ -112,6 +342,7 @@ impl ExGrid {
     /// Matters only in Grid view
     #[inline]
     pub fn min_col_width(mut self, min_col_width: f32) -> Self {
+        self.min_col_width = Some(min_col_width);
         self.grid = self.grid.min_col_width(min_col_width);
         self
     }
@@ -121,6 +352,7 @@ impl ExGrid {
     /// Matters only in Grid view
     #[inline]
     pub fn min_row_height(mut self, min_row_height: f32) -> Self {
+        self.min_row_height = Some(min_row_height);
         self.grid = self.grid.min_row_height(min_row_height);
         self
     }
@@ -129,7 +361,7 @@ impl ExGrid {
     /// Matters only in Grid view
     #[inline]
     pub fn max_col_width(mut self, max_col_width: f32) -> Self {
-        self.grid = self.grid.max_col_width(max_col_width);
+        self.max_col_width = Some(max_col_width);
         self
     }
 
@@ -138,6 +370,8 @@ impl ExGrid {
     /// Matters only in Grid view
     #[inline]
     pub fn spacing(mut self, spacing: impl Into<Vec2>) -> Self {
+        let spacing = spacing.into();
+        self.spacing = Some(spacing);
         self.grid = self.grid.spacing(spacing);
         self
     }
@@ -147,6 +381,7 @@ impl ExGrid {
     /// Matters only in Grid view
     #[inline]
     pub fn start_row(mut self, start_row: usize) -> Self {
+        self.start_row = start_row;
         self.grid = self.grid.start_row(start_row);
         self
     }
@@ -158,44 +393,668 @@ impl ExGrid {
         self.mode = mode;
         self
     }
+
+    /// Frame surrounding each row in `CompactWidth` layout.
+    /// Default: [`Frame::group`]
+    #[inline]
+    pub fn compact_frame(self, frame: Frame) -> Self {
+        self.compact_frame_fn(move |_depth, _style| frame)
+    }
+
+    /// Setting this will allow for dynamic selection of frame surrounding each row in `CompactWidth` layout,
+    /// `frame_picker` receives nesting level of the row (`0` for top level rows) and current style.
+    /// Default: [`Frame::group`]
+    #[inline]
+    pub fn compact_frame_fn<F>(mut self, frame_picker: F) -> Self
+    where
+        F: Send + Sync + Fn(usize, &Style) -> Frame + 'static,
+    {
+        self.compact_frame = Some(Box::new(frame_picker));
+        self
+    }
+
+    /// Names of the columns, in `CompactWidth` layout they are used to label values (see [`Self::compact_labels`]).
+    /// Empty name leaves column unlabeled.
+    #[inline]
+    pub fn column_names<T: Into<String>>(mut self, names: impl IntoIterator<Item = T>) -> Self {
+        self.column_names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// How values are labeled with column names in `CompactWidth` layout.
+    /// Default: [`CompactLabels::Inline`]
+    #[inline]
+    pub fn compact_labels(mut self, compact_labels: CompactLabels) -> Self {
+        self.compact_labels = compact_labels;
+        self
+    }
+
+    /// Width margin (in points) used by automatic modes (eg. [`GridMode::Auto0Wrap`]) when going back from `CompactWidth` to `Traditional` layout.
+    /// Traditional layout is restored only when available width exceeds grid width by at least this value,
+    /// which prevents flickering between layouts when the window width is near the boundary.
+    /// Default: `16.0`
+    #[inline]
+    pub fn auto_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.auto_hysteresis = hysteresis;
+        self
+    }
+
+    /// Show search box above the grid, it filters rows in the same way as [`Self::filter`]
+    #[inline]
+    pub fn search_box(mut self, search_box: bool) -> Self {
+        self.search_box = search_box;
+        self
+    }
+
+    /// Show only rows in which text of some [`ExUi::extext`] cell contains `query` (case insensitive)
+    /// and collapsing rows with such subrows (they are shown expanded), matching text is highlighted.
+    /// Empty query shows all rows. It replaces text of the search box (see [`Self::search_box`]).
+    #[inline]
+    pub fn filter(mut self, query: impl Into<String>) -> Self {
+        self.filter = Some(query.into());
+        self
+    }
+
+    /// Allow selecting rows (selected rows are highlighted and returned in [`ExGridResponse::selected`])
+    /// Default: [`SelectionMode::None`]
+    #[inline]
+    pub fn selection(mut self, selection: SelectionMode) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Allow resizing columns by dragging separators between them (Traditional mode only),
+    /// double-click on the separator makes column fit its content again.
+    /// Widths are kept in memory (see also [`ExUi::column_widths`] and [`ExUi::set_column_width`]).
+    /// Default: `false`
+    #[inline]
+    pub fn resizable_columns(mut self, resizable: bool) -> Self {
+        self.resizable_columns = resizable;
+        self
+    }
+
+    /// Keep collapse state of rows with persisted data (see [`egui::Memory::data`]), so it survives restarts of the app.
+    /// State of rows identified by key (see [`ExUi::row_key`]) is kept under their key,
+    /// so it is not lost when rows are inserted or removed.
+    /// Default: `false`
+    #[cfg(feature = "persistence")]
+    #[inline]
+    pub fn persist_collapsed(mut self, persist: bool) -> Self {
+        self.persist_collapsed = persist;
+        self
+    }
+
+    /// Add buttons expanding and collapsing all rows to the first cell of the header row (see [`ExUi::header_row`]).
+    /// Default: `false`
+    #[inline]
+    pub fn expand_buttons(mut self, expand_buttons: bool) -> Self {
+        self.expand_buttons = expand_buttons;
+        self
+    }
+
+    /// Animate expanding and collapsing of subdata rows (like `egui::CollapsingHeader`).
+    /// Height of the rows is known after they were shown, so their first expanding is not animated.
+    /// Default: `true`
+    #[inline]
+    pub fn animate(mut self, animate: bool) -> Self {
+        self.animate = animate;
+        self
+    }
+
+    /// Replace button toggling collapsing rows ("⏵"/"⏷") with custom icon painted by `icon_fn`
+    /// (like `egui::CollapsingHeader::icon`). It gets openness of the rows (0.0 - collapsed, 1.0 - expanded)
+    /// and response of the icon, eg. `egui::collapsing_header::paint_default_icon`
+    #[inline]
+    pub fn collapse_icon(mut self, icon_fn: fn(&mut Ui, f32, &Response)) -> Self {
+        self.collapse_icon = Some(icon_fn);
+        self
+    }
+
+    /// Set content shown in place of collapsed rows.
+    /// Matters only in `CompactWidth` layout. Default: "⚫⚫⚫" label
+    #[inline]
+    pub fn collapsed_placeholder<F>(mut self, add_placeholder: F) -> Self
+    where
+        F: Send + Sync + Fn(&mut Ui) + 'static,
+    {
+        self.collapsed_placeholder = Some(Box::new(add_placeholder));
+        self
+    }
+
+    /// Clicking anywhere in the row of collapsing header (not only on its icon) toggles its rows.
+    /// Widgets added directly with [`Ui`] methods keep clicks they sense (same as for [`ExUi::row_context_menu`]).
+    /// Default: `false`
+    #[inline]
+    pub fn clickable_headers(mut self, clickable_headers: bool) -> Self {
+        self.clickable_headers = clickable_headers;
+        self
+    }
+
+    /// Set width of indentation of each nesting level of rows, tree guides connecting nested rows are painted in it.
+    /// Default: [`crate::style::Spacing::indent`].
+    /// Matters only in Grid view
+    #[inline]
+    pub fn indent_width(mut self, indent_width: f32) -> Self {
+        self.indent_width = Some(indent_width);
+        self
+    }
+
+    /// Configure width, alignment and wrapping of cells of `column` (numbered from 0).
+    /// Resizing the column (see [`Self::resizable_columns`]) keeps its width within `min`..`max`
+    #[inline]
+    pub fn column(mut self, column: usize, spec: ColumnSpec) -> Self {
+        if self.columns.len() <= column {
+            self.columns.resize(column + 1, Default::default());
+        }
+        self.columns[column] = spec;
+        self
+    }
+
+    /// Minimal width to which columns can be shrunk (by wrapping their content) in [`GridMode::AutoOptimalWrap`],
+    /// if grid does not fit even then, `CompactWidth` layout is used.
+    /// Default: `100.0`
+    #[inline]
+    pub fn min_wrap_width(mut self, min_wrap_width: f32) -> Self {
+        self.min_wrap_width = min_wrap_width;
+        self
+    }
+}
+
+/// Layout decision of automatic [`GridMode`]s, persisted between frames.
+#[derive(Clone, Debug, Default)]
+struct AutoModeState {
+    /// Layout used in the previous frame was `CompactWidth`.
+    compact: bool,
+    /// Width of the grid when it was last shown in `Traditional` layout.
+    traditional_width: Option<f32>,
+    /// Natural (not wrapped) width of each column content (`AutoOptimalWrap` only).
+    col_widths: Vec<f32>,
+    /// Width below which column content could not be shrunk by wrapping (`AutoOptimalWrap` only).
+    col_min_widths: Vec<f32>,
+    /// Width of each column content measured in previous frame (`AutoOptimalWrap` only).
+    col_wrapped_widths: Vec<f32>,
+    /// Wrapping width used in previous frame (`AutoOptimalWrap` only).
+    wrap_width: f32,
+    /// Width of the grid in `CompactWidth` layout and width available for it, once they are the same in two frames in a row,
+    /// if grid gets narrower while available width does not, its content shrank and `Traditional` layout is measured again.
+    compact_width: Option<(f32, f32)>,
+    /// Width of the grid in `CompactWidth` layout and width available for it in the previous frame
+    /// (right after layout change grid is still as wide as in the previous layout).
+    compact_width_prev: Option<(f32, f32)>,
 }
 
 impl ExGrid {
+    /// Resolve automatic [`GridMode`]s into layout that should be used in this frame.
+    /// Returns `None` for `CompactWidth`, otherwise wrapping width for `Traditional` layout (may be infinite)
+    fn resolve_mode(&self, ui: &Ui, state: &AutoModeState, available_width: f32) -> Option<f32> {
+        let max_col_width = self.max_col_width.unwrap_or(f32::INFINITY);
+        let margin = if state.compact {
+            self.auto_hysteresis
+        } else {
+            0.0
+        };
+        match self.mode {
+            GridMode::CompactWidth => None,
+            GridMode::Traditional => Some(max_col_width),
+            GridMode::Auto0Wrap => match state.traditional_width {
+                // nothing measured yet, show traditional layout to get its width
+                None => Some(max_col_width),
+                Some(width) if width + margin > available_width => None,
+                Some(_) => Some(max_col_width),
+            },
+            GridMode::AutoOptimalWrap => {
+                if state.col_widths.is_empty() {
+                    return Some(max_col_width);
+                }
+                let min_col_width = self.min_col_width.unwrap_or(ui.spacing().interact_size.x);
+                let spacing = self.spacing.map_or(ui.spacing().item_spacing.x, |s| s.x);
+                let grid_width = |wrap_width: f32| {
+                    let columns = state.col_widths.iter().enumerate();
+                    let cells: f32 = columns
+                        .map(|(i, width)| {
+                            let min_width = state.col_min_widths.get(i).copied().unwrap_or(0.0);
+                            width.min(wrap_width).max(min_width).max(min_col_width)
+                        })
+                        .sum();
+                    cells + (state.col_widths.len() - 1) as f32 * spacing + margin
+                };
+                if grid_width(max_col_width) <= available_width {
+                    return Some(max_col_width);
+                }
+                let widest = state.col_widths.iter().copied().fold(0.0, f32::max);
+                let (mut fits, mut overflows) = (self.min_wrap_width, widest.min(max_col_width));
+                if grid_width(fits) > available_width {
+                    return None;
+                }
+                // find widest wrapping width that allows grid to fit
+                for _ in 0..16 {
+                    let mid = (fits + overflows) / 2.0;
+                    if grid_width(mid) <= available_width {
+                        fits = mid;
+                    } else {
+                        overflows = mid;
+                    }
+                }
+                Some(fits.floor())
+            }
+        }
+    }
+
     /// Show `ExGrid` in supplied `ui` and add `add_contents` to it.
     pub fn show<R>(
         self,
         ui: &mut Ui,
         add_contents: impl FnOnce(&mut ExUi) -> R,
-    ) -> InnerResponse<R> {
-        if self.mode == GridMode::Traditional {
+    ) -> ExGridResponse<R> {
+        self.show_impl(ui, None, |ui, _| add_contents(ui))
+    }
+
+    /// Show only those of `total_rows` top level rows that are visible (eg. in [`ScrollArea`]),
+    /// rows that are not shown are replaced with empty space.
+    ///
+    /// `add_contents` should add top level rows from the given range, each together with its subrows (if any).
+    /// Height of each row (including its expanded subrows) is remembered when the row is shown,
+    /// rows that were not shown yet are assumed to be `row_height` high.
+    /// Nested rows are identified by the index of top level row, so their collapse state does not depend on scrolling.
+    ///
+    /// [`ExUi::header_row`] (if used) should be added before rows, it does not count into `total_rows`.
+    /// Widgets that keep their state should use [`ExUi::make_persistent_id`], as automatic ids change with scrolling.
+    ///
+    pub fn show_rows<R>(
+        self,
+        ui: &mut Ui,
+        row_height: f32,
+        total_rows: usize,
+        add_contents: impl FnOnce(&mut ExUi, std::ops::Range<usize>) -> R,
+    ) -> ExGridResponse<R> {
+        self.show_impl(ui, Some((row_height, total_rows)), add_contents)
+    }
+
+    /// Expand all collapsing rows of the grid `grid_id` (see [`ExGridResponse::id`]) next time it is shown
+    pub fn expand_all(ctx: &Context, grid_id: Id) {
+        Self::expand_to_depth(ctx, grid_id, usize::MAX);
+    }
+
+    /// Collapse all collapsing rows of the grid `grid_id` (see [`ExGridResponse::id`]) next time it is shown
+    pub fn collapse_all(ctx: &Context, grid_id: Id) {
+        Self::expand_to_depth(ctx, grid_id, 0);
+    }
+
+    /// Expand collapsing rows of the grid `grid_id` (see [`ExGridResponse::id`]) nested less than `depth` levels deep
+    /// and collapse the others next time the grid is shown (eg. `1` expands only top level rows),
+    /// with [`Self::show_rows`] rows out of view are collapsed/expanded when they are scrolled into view
+    pub fn expand_to_depth(ctx: &Context, grid_id: Id, depth: usize) {
+        CollapseRequest::update(ctx, grid_id, |request| request.expand_to_depth(depth));
+    }
+
+    /// Collapse or expand rows of the grid `grid_id` (see [`ExGridResponse::id`]) identified by `row_key`
+    /// (see [`ExUi::collapsing_rows_keyed`]) next time the grid is shown
+    pub fn set_collapsed(
+        ctx: &Context,
+        grid_id: Id,
+        row_key: impl std::hash::Hash,
+        collapsed: bool,
+    ) {
+        let id = keyed_header_id(grid_id, row_key);
+        CollapseRequest::set_collapsed(ctx, grid_id, id, collapsed);
+    }
+
+    /// Shared implementation of [`Self::show`] and [`Self::show_rows`] (`rows` is `Some` only for the latter)
+    fn show_impl<R>(
+        mut self,
+        ui: &mut Ui,
+        rows: Option<(f32, usize)>,
+        add_contents: impl FnOnce(&mut ExUi, std::ops::Range<usize>) -> R,
+    ) -> ExGridResponse<R> {
+        let grid_id = ui.make_persistent_id(self.id_source);
+        let auto_id = grid_id.with("auto_mode");
+        let available_width = ui.available_width();
+        let auto = matches!(self.mode, GridMode::Auto0Wrap | GridMode::AutoOptimalWrap);
+        let mut state: AutoModeState = if auto {
+            ui.data_mut(|d| d.get_temp(auto_id)).unwrap_or_default()
+        } else {
+            Default::default()
+        };
+        let wrap_width = self.resolve_mode(ui, &state, available_width);
+
+        let filter_id = ui.make_persistent_id(self.id_source).with("filter");
+        let mut filter: RowFilter =
+            ui.data_mut(|d| std::mem::take(d.get_temp_mut_or_default(filter_id)));
+        if let Some(query) = self.filter.take() {
+            filter.text = query;
+        }
+        if self.search_box {
+            ui.add(TextEdit::singleline(&mut filter.text).hint_text("🔍 Search"));
+        }
+        let query = to_lowercase(filter.text.trim());
+        if query != filter.query {
+            // rows shown for previous query are still used, until matches are found
+            filter.query = query;
+            ui.ctx().request_repaint();
+        }
+        let mut filter = Some(filter);
+
+        let sort_id = ui.make_persistent_id(self.id_source).with("sort");
+        let sort: Option<ColumnSort> = ui.data_mut(|d| d.get_temp(sort_id)).flatten();
+        let rows_id = ui.make_persistent_id(self.id_source).with("rows");
+        let mut rows_memory = None;
+        let mut range = 0..0;
+        let mut after = 0.0;
+        if let Some((row_height, total_rows)) = rows {
+            // taken out of memory (rather than cloned), as it holds entry for every row
+            let mut memory: RowsMemory =
+                ui.data_mut(|d| std::mem::take(d.get_temp_mut_or_default(rows_id)));
+            let query = filter.as_ref().map(|f| f.query.clone()).unwrap_or_default();
+            if memory.compact != wrap_width.is_none()
+                || memory.query != query
+                || memory.sort != sort
+            {
+                // rows heights differ between layouts and filters, and sorting moves rows to other positions
+                memory = RowsMemory {
+                    compact: wrap_width.is_none(),
+                    query,
+                    sort,
+                    ..Default::default()
+                };
+            }
+            memory.heights.resize(total_rows, f32::NAN);
+            memory.rows.resize(total_rows, 1);
+            let estimate = row_height + ui.spacing().item_spacing.y;
+            let height = |i: usize| match memory.heights[i] {
+                h if h.is_nan() => estimate,
+                h => h,
+            };
+            let clip_rect = ui.clip_rect();
+            let top = ui.cursor().top();
+            let mut y = top + memory.header;
+            let mut start = 0;
+            let mut start_row = 0;
+            while start < total_rows && y + height(start) < clip_rect.top() {
+                y += height(start);
+                start_row += memory.rows[start];
+                start += 1;
+            }
+            let before = y - top - memory.header;
+            let mut end = start;
+            while end < total_rows && y < clip_rect.bottom() {
+                y += height(end);
+                end += 1;
+            }
+            after = (end..total_rows).map(height).sum();
+            range = start..end;
+            self.start_row += start_row;
+            self.grid = self.grid.start_row(self.start_row);
+            rows_memory = Some(memory);
+            ui.add_space(before);
+        }
+        let selection_id = ui.make_persistent_id(self.id_source).with("selection");
+        let mut selection = match self.selection {
+            SelectionMode::None => None,
+            mode => {
+                let multi = mode == SelectionMode::Multi;
+                let selection: Option<Selection> = ui.data_mut(|d| d.get_temp(selection_id));
+                let mut selection = selection.unwrap_or_default();
+                selection.multi = multi;
+                Some(selection)
+            }
+        };
+        let widths_id = ui.make_persistent_id(self.id_source).with("column_widths");
+        let column_widths: Vec<Option<f32>> = ui
+            .data_mut(|d| d.get_persisted(widths_id))
+            .unwrap_or_default();
+        let resizable_columns = self.resizable_columns;
+        let min_col_width = self.min_col_width;
+        let columns = self.columns;
+        let persist_collapsed = self.persist_collapsed;
+        let expand_buttons = self.expand_buttons;
+        let animate = self.animate;
+        let collapse_icon = self.collapse_icon;
+        let clickable_headers = self.clickable_headers;
+        let indent_width = self.indent_width;
+        let mut collapse_request = CollapseRequest::take(ui.ctx(), grid_id);
+        // `set_collapsed` stores state of rows where grid reads it from
+        ui.data_mut(|d| d.insert_temp(grid_id.with("persist_collapsed"), persist_collapsed));
+        let all_rows_shown = match rows {
+            Some((_, total_rows)) => range.start == 0 && range.end >= total_rows,
+            None => true,
+        };
+        let first_row = self.start_row;
+        let first_block = range.start;
+        let mut row_blocks = None;
+        let mut filter_out = None;
+        let mut selection_out: Option<Selection> = None;
+        let mut prepare = |ex: &mut ExUi| {
+            if filter.as_ref().is_some_and(|f| !f.query.is_empty()) {
+                ex.state.filter = filter.take();
+            }
+            ex.state.sort = sort;
+            ex.state.selection = selection.take();
+            #[cfg(not(any(feature = "egui23", feature = "egui24", feature = "egui25")))]
+            if ex.state.selection.is_some() {
+                // selectable labels would swallow clicks meant for selecting the row
+                ex.ui.style_mut().interaction.selectable_labels = false;
+            }
+            ex.state.row = first_row;
+            ex.state.first_row = first_row;
+            ex.state.column_widths = column_widths.clone();
+            ex.state.columns = columns.clone();
+            ex.state.persist_collapsed = persist_collapsed;
+            ex.state.expand_buttons = expand_buttons;
+            ex.state.animate = animate;
+            ex.state.collapse_icon = collapse_icon;
+            ex.state.clickable_headers = clickable_headers;
+            ex.state.indent_width = indent_width.unwrap_or(ex.spacing().indent);
+            ex.state.collapse_request = collapse_request.take();
+            ex.state.grid_id = grid_id;
+            ex.state.min_col_width = min_col_width.unwrap_or(ex.spacing().interact_size.x);
+            if rows.is_some() {
+                ex.state.row_cursor[0] = first_block;
+                let top = ex.ui.cursor().top();
+                ex.state.row_blocks = Some(RowBlocks::new(first_block, top, first_row));
+            }
+        };
+        let finish = |ex: &mut ExUi| {
+            // last row is often left open, it is ended before filter and selection state is taken out
+            if ex.state.column != 0 {
+                ex.end_row();
+            }
+            if ex.state.sort != sort {
+                let sort = ex.state.sort;
+                ex.data_mut(|d| d.insert_temp(sort_id, sort));
+            }
+            if resizable_columns {
+                ex.column_resize_handles();
+            }
+            if ex.state.column_widths != column_widths {
+                let widths = ex.state.column_widths.clone();
+                ex.data_mut(|d| d.insert_persisted(widths_id, widths));
+                ex.ctx().request_repaint();
+            }
+            let ctx = ex.ctx().clone();
+            if let Some(ref mut filter) = ex.state.filter {
+                if filter.shown != filter.shown_next || filter.open != filter.open_next {
+                    ctx.request_repaint();
+                }
+                filter.shown = std::mem::take(&mut filter.shown_next);
+                filter.open = std::mem::take(&mut filter.open_next);
+            }
+            if let Some(ref mut selection) = ex.state.selection {
+                selection.finish();
+            }
+            if let Some(request) = ex.state.collapse_request.take() {
+                request.keep(&ctx, grid_id, all_rows_shown);
+            }
+            ex.finish_validation();
+            let selection = ex.state.selection.take();
+            (
+                ex.state.row_blocks.take(),
+                ex.state.filter.take(),
+                selection,
+            )
+        };
+
+        let min_row_height = self.min_row_height;
+        let compact_frame = self.compact_frame;
+        let collapsed_placeholder = self.collapsed_placeholder;
+        let column_names = self.column_names;
+        let compact_labels = self.compact_labels;
+        let mut color_picker = self.color_picker;
+        let mut grid = self.grid;
+        let mut col_widths = Vec::new();
+        let ret = if let Some(wrap_width) = wrap_width {
             let add_contents = |ui: &mut Ui| {
                 let id = ui.id();
-                let mut ex = ui.into();
-                let ret = add_contents(&mut ex);
-                ex.data_mut(|d| d.insert_temp(id, ex.state.width_max));
+                let mut ex: ExUi<'_, '_> = ui.into();
+                prepare(&mut ex);
+                if let Some(min_row_height) = min_row_height {
+                    ex.state.min_row_height = min_row_height;
+                }
+                let ret = add_contents(&mut ex, range);
+                (row_blocks, filter_out, selection_out) = finish(&mut ex);
+                ex.store_memory(id);
+                col_widths = std::mem::take(&mut ex.state.col_widths);
                 ret
             };
-            self.grid.show(ui, add_contents)
+            if let Some(color_picker) = color_picker {
+                grid = grid.with_row_color(move |row, style| color_picker(row, style));
+            }
+            // minimal height of the row is kept by `ExUi`, so that rows revealed by animation can be lower
+            let grid = grid.max_col_width(wrap_width).min_row_height(0.0);
+            let ret = grid.show(ui, add_contents);
+            if auto {
+                let width = ret.response.rect.width();
+                if state.compact || width > available_width {
+                    ui.ctx().request_repaint();
+                }
+                state.compact = false;
+                state.compact_width = None;
+                state.compact_width_prev = None;
+                state.traditional_width = Some(width);
+                state.col_widths.resize(col_widths.len(), 0.0);
+                state.col_min_widths.resize(col_widths.len(), 0.0);
+                state.col_wrapped_widths.resize(col_widths.len(), 0.0);
+                let same_wrap = (state.wrap_width - wrap_width).abs() < 0.5;
+                for (i, width) in col_widths.into_iter().enumerate() {
+                    let wrapped_prev = std::mem::replace(&mut state.col_wrapped_widths[i], width);
+                    if state.col_widths[i] < wrap_width - 0.5 {
+                        // column was not affected by wrapping, so this is its natural width
+                        state.col_widths[i] = width;
+                    } else if same_wrap && (width - wrapped_prev).abs() > 0.5 {
+                        // content of wrapped column changed, its natural width has to be measured again
+                        state.col_widths[i] = width;
+                    } else {
+                        // wrapped content is narrower than natural one
+                        state.col_widths[i] = state.col_widths[i].max(width);
+                    }
+                    // content that could not be shrunk by wrapping
+                    state.col_min_widths[i] = if width > wrap_width + 0.5 { width } else { 0.0 };
+                }
+                state.wrap_width = wrap_width;
+                ui.data_mut(|d| d.insert_temp(auto_id, state));
+            }
+            ret
         } else {
             let add_contents = |ui: &mut Ui| {
                 let id = ui.id();
                 let mut ex: ExUi<'_, '_> = ui.into();
+                prepare(&mut ex);
+                ex.state.compact_frame = compact_frame;
+                ex.state.collapsed_placeholder = collapsed_placeholder;
+                ex.state.column_names = column_names;
+                ex.state.compact_labels = compact_labels;
+                if color_picker.is_none() && ex.ui.visuals().striped {
+                    color_picker = Some(Arc::new(striped_row_color));
+                }
+                ex.state.color_picker = color_picker;
+                let frame = ex.state.compact_frame(0, ex.ui.style());
                 ex.state.mode = ExUiMode::Compact {
-                    ui_row: vec![FrameRun::begin(Frame::group(ex.ui.style()), 0, &mut ex.ui)],
+                    ui_row: vec![FrameRun::begin(frame, 1, &mut ex.ui)],
                     ui_columns: None,
                 };
-                let ret = add_contents(&mut ex);
-                if ex.state.column != 0 {
-                    ex.end_row()
-                }
-                ex.data_mut(|d| d.insert_temp(id, ex.state.width_max));
+                let ret = add_contents(&mut ex, range);
+                (row_blocks, filter_out, selection_out) = finish(&mut ex);
+                ex.store_memory(id);
                 ret
             };
-            self.grid.num_columns(1).show(ui, add_contents)
+            let ret = grid.striped(false).num_columns(1).show(ui, add_contents);
+            if auto {
+                let width = ret.response.rect.width();
+                let shrunk = state
+                    .compact_width
+                    .is_some_and(|(width_prev, available_prev)| {
+                        width < width_prev - 0.5 && available_width >= available_prev - 0.5
+                    });
+                if shrunk {
+                    state.traditional_width = None;
+                    state.col_widths.clear();
+                }
+                if !state.compact || shrunk {
+                    ui.ctx().request_repaint();
+                }
+                let measured = (width, available_width);
+                let stable = matches!(state.compact_width_prev, Some((width_prev, available_prev))
+                    if (width - width_prev).abs() < 0.5 && (available_width - available_prev).abs() < 0.5);
+                if stable {
+                    state.compact_width = Some(measured);
+                }
+                state.compact_width_prev = Some(measured);
+                state.compact = true;
+                ui.data_mut(|d| d.insert_temp(auto_id, state));
+            }
+            ret
+        };
+
+        if let (Some(mut memory), Some(row_blocks)) = (rows_memory, row_blocks) {
+            memory.header = row_blocks.header.unwrap_or(0.0);
+            let mut changed = false;
+            for (index, height, rows) in row_blocks.measured {
+                if index < memory.heights.len() {
+                    let known = memory.heights[index];
+                    changed |= known.is_nan() || (known - height).abs() >= 0.5;
+                    memory.heights[index] = height;
+                    memory.rows[index] = rows;
+                }
+            }
+            if changed {
+                // positions of rows below are estimated better now
+                ui.ctx().request_repaint();
+            }
+            ui.add_space(after);
+            ui.data_mut(|d| d.insert_temp(rows_id, memory));
+        }
+        // filter is kept (even if not used), as it stores text of the search box
+        let filter = filter_out.or(filter).unwrap_or_default();
+        ui.data_mut(|d| d.insert_temp(filter_id, filter));
+        let selected = selection_out.as_ref().map(|s| s.selected.clone());
+        if let Some(selection) = selection_out {
+            ui.data_mut(|d| d.insert_temp(selection_id, selection));
+        }
+        ExGridResponse {
+            inner: ret.inner,
+            response: ret.response,
+            id: grid_id,
+            selected: selected.unwrap_or_default(),
         }
     }
 }
 
+/// Heights of top level rows shown by [`ExGrid::show_rows`], persisted between frames.
+#[derive(Clone, Debug, Default)]
+struct RowsMemory {
+    /// Heights were measured in `CompactWidth` layout
+    compact: bool,
+    /// Query of filter used when heights were measured
+    query: String,
+    /// Sorting of rows when heights were measured
+    sort: Option<ColumnSort>,
+    /// Height of the header row
+    header: f32,
+    /// Height (spacing included) of each top level row, `NAN` if it was not measured yet
+    heights: Vec<f32>,
+    /// Number of `egui::Grid` rows spanned by each top level row
+    rows: Vec<usize>,
+}
+
 /// Similar to `egui::containers::frame::Prepared`, but this is necessary as:
 /// - `frame` module is private
 /// - `Prepared::end(..)` requires owned self
@@ -85,10 +85,32 @@ impl<'a, 'b: 'a> ExUi<'a, 'b> {
 /// # [`Id`] creation
 impl<'a, 'b> ExUi<'a, 'b> {
     /// A unique identity of this [`ExUi`].
-    /// Differently to [`egui::UI::id`], it changes as widgets are added to it (it is calculated based on row number on each nesting level).
+    /// Differently to [`egui::UI::id`], it changes as widgets are added to it (it is calculated based on row number on each nesting level or on key of the row, see [`Self::row_key`]).
     #[inline]
     pub fn id(&self) -> Id {
-        self.ui.id().with(self.state.row_cursor.as_slice())
+        self.cursor_id(&self.state.row_cursor)
+    }
+    /// Id of the row at `row_cursor` nesting cursor (see [`Self::id`])
+    pub(crate) fn cursor_id(&self, row_cursor: &[usize]) -> Id {
+        // rows nested in the row with key (see `Self::row_key`) are identified relatively to it
+        let current = self.state.row_cursor.iter();
+        let keyed = (row_cursor.iter().zip(current).zip(&self.state.row_keys))
+            .take_while(|((row, current), _)| row == current)
+            .enumerate()
+            .filter_map(|(level, (_, key))| key.map(|key| (level, key)))
+            .last();
+        if let Some((level, key)) = keyed {
+            return key.with(&row_cursor[level + 1..]);
+        }
+        let sort_order = self.state.sort_order.as_ref();
+        match sort_order.and_then(|order| order.get(row_cursor[0])) {
+            Some(&row) => {
+                let mut row_cursor = row_cursor.to_vec();
+                row_cursor[0] = row;
+                self.ui.id().with(row_cursor.as_slice())
+            }
+            None => self.ui.id().with(row_cursor),
+        }
     }
     /// Use this to generate widget ids for widgets that have persistent state in [`Memory`].
     pub fn make_persistent_id<IdSource>(&self, id_source: IdSource) -> Id
@@ -118,7 +140,12 @@ impl<'a, 'b> ExUi<'a, 'b> {
     /// ```
     #[inline]
     pub fn add(&mut self, widget: impl Widget) -> Response {
-        self.add_ex_opt(|ui| widget.ui(ui))
-            .unwrap_or_else(|| self.dummy_response())
+        match self.add_ex_opt(|ui| widget.ui(ui)) {
+            Some(response) => {
+                self.add_row_response(&response);
+                response
+            }
+            None => self.dummy_response(),
+        }
     }
 }